@@ -15,25 +15,168 @@
 //!```
 
 use hdf5::types::FixedAscii;
+use std::collections::HashMap;
 
 pub enum Error {
     HDF5(hdf5::Error),
+    Io(std::io::Error),
     Dataset(String),
     Group(String),
     Struct,
+    NotMatlab(String),
+    Pattern(String),
+    Sparse(String),
+    Field(String),
+    Class {
+        name: String,
+        expected: String,
+        found: String,
+    },
+    Shape {
+        expected: String,
+        found: Vec<usize>,
+    },
+    Type {
+        name: String,
+        expected: String,
+        found: String,
+    },
+    Reader {
+        name: String,
+        kind: String,
+        reader: String,
+    },
+    ExternalLink {
+        name: String,
+        file: String,
+    },
+    Superblock {
+        file: String,
+        version: u8,
+    },
+    Verify {
+        name: String,
+        error: Box<Error>,
+    },
+    Overflow {
+        index: usize,
+        value: String,
+    },
+    NonFinite {
+        name: String,
+        indices: Vec<usize>,
+    },
+    Object {
+        name: String,
+        class: String,
+        decode: i32,
+    },
+    Stride(String),
+    ColormapIndex {
+        name: String,
+        index: usize,
+        value: f64,
+        n_color: usize,
+    },
+    #[cfg(feature = "arrow")]
+    Arrow(arrow::error::ArrowError),
 }
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Error::HDF5(e) => write!(
+            Error::HDF5(e) => match <Self as std::error::Error>::source(self) {
+                Some(source) => write!(
+                    f,
+                    "Importing Matlab variables failed ({}), caused by {}",
+                    e, source
+                ),
+                None => write!(f, "Importing Matlab variables failed ({})", e),
+            },
+            Error::Io(e) => write!(f, "Reading Matlab file failed: {}", e),
+            Error::Overflow { index, value } => write!(
+                f,
+                "Element {} of value {} does not fit in the requested type",
+                index, value
+            ),
+            Error::NonFinite { name, indices } => write!(
+                f,
+                "Matlab array {} has NaN or infinite elements at the linear indices {:?}",
+                name, indices
+            ),
+            Error::Object {
+                name,
+                class,
+                decode,
+            } => write!(
+                f,
+                "Matlab variable {} is a {} object serialized with MATLAB_object_decode {}; use File::read",
+                name, class, decode
+            ),
+            Error::ColormapIndex {
+                name,
+                index,
+                value,
+                n_color,
+            } => write!(
                 f,
-                "Importing Matlab variables failed ({}), caused by {}",
-                e,
-                <Self as std::error::Error>::source(self).unwrap()
+                "Element {} of {} is {}, not an index of the {} colors of the colormap",
+                index, name, value, n_color
             ),
+            Error::Stride(name) => write!(f, "Reading {} with a stride of 0 is not possible", name),
+            Error::Verify { name, error } => write!(f, "Verifying {} failed: {}", name, error),
+            #[cfg(feature = "arrow")]
+            Error::Arrow(e) => write!(f, "Creating Arrow record batch failed: {}", e),
             Error::Dataset(name) => write!(f, "Loading {} dataset failed", name),
             Error::Group(name) => write!(f, "Loading {} group failed", name),
             Error::Struct => write!(f, "Matlab class is not a struct"),
+            Error::Pattern(pattern) => write!(f, "Invalid glob pattern {}", pattern),
+            Error::NotMatlab(name) => write!(
+                f,
+                "HDF5 group {} has no MATLAB_class attribute; it may not be a Matlab variable",
+                name
+            ),
+            Error::Sparse(name) => write!(f, "Matlab variable {} is not a sparse matrix", name),
+            Error::Field(name) => write!(f, "Matlab struct has no {} field", name),
+            Error::Reader { name, kind, reader } => {
+                write!(
+                    f,
+                    "Matlab variable {} is {}; use File::{}",
+                    name, kind, reader
+                )
+            }
+            Error::Superblock { file, version } => write!(
+                f,
+                "HDF5 superblock version {} of {} is not supported by the HDF5 library",
+                version, file
+            ),
+            Error::ExternalLink { name, file } => write!(
+                f,
+                "Matlab variable {} links to the missing file {}",
+                name, file
+            ),
+            Error::Class {
+                name,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Matlab class of {} is {}, expected {}",
+                name, found, expected
+            ),
+            Error::Shape { expected, found } => write!(
+                f,
+                "Matlab array shape {:?} does not match the expected {}",
+                found, expected
+            ),
+            Error::Type {
+                name,
+                expected,
+                found,
+            } => write!(
+                f,
+                "HDF5 type of {} is {}, expected {}",
+                name, found, expected
+            ),
         }
     }
 }
@@ -46,6 +189,10 @@ impl std::error::Error for Error {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match &self {
             Error::HDF5(e) => e.source(),
+            Error::Io(e) => Some(e),
+            Error::Verify { error, .. } => Some(error.as_ref()),
+            #[cfg(feature = "arrow")]
+            Error::Arrow(e) => Some(e),
             _ => None,
         }
     }
@@ -55,6 +202,17 @@ impl From<hdf5::Error> for Error {
         Error::HDF5(error)
     }
 }
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Error::Io(error)
+    }
+}
+#[cfg(feature = "arrow")]
+impl From<arrow::error::ArrowError> for Error {
+    fn from(error: arrow::error::ArrowError) -> Self {
+        Error::Arrow(error)
+    }
+}
 pub type Result<T> = ::std::result::Result<T, Error>;
 
 /// Matlab variables
@@ -68,118 +226,5249 @@ impl<T> MatVar<T> {
     pub fn raw(self) -> Vec<T> {
         self.data
     }
+    /// Returns the elements in Matlab linear index order, element `k` being Matlab `A(k+1)`
+    ///
+    /// The HDF5 dataset is the row-major array of the reversed Matlab shape, so its elements already are in
+    /// Matlab column-major order and this is the same as [MatVar::raw]:
+    /// `A = [1 2 3; 4 5 6]` is stored with the HDF5 shape `[3, 2]` and returns `[1, 4, 2, 5, 3, 6]`
+    pub fn matlab_linear_order(self) -> Vec<T> {
+        self.data
+    }
     pub fn shape(&self) -> &[usize] {
         &self.shape
     }
     pub fn name(&self) -> &str {
         &self.name
     }
+    /// Returns the shape of the HDF5 dataset, same as [MatVar::shape]
+    pub fn hdf5_shape(&self) -> &[usize] {
+        &self.shape
+    }
+    /// Returns the shape in Matlab order, i.e. the reversed HDF5 shape with at least 2 dimensions
+    pub fn matlab_shape(&self) -> Vec<usize> {
+        matlab_shape(&self.shape)
+    }
+    /// Returns the Matlab number of rows and columns of a 2D array
+    ///
+    /// Trailing singleton dimensions are ignored, returns [Error::Shape] for other arrays
+    pub fn dims(&self) -> Result<(usize, usize)> {
+        let shape = self.matlab_shape();
+        if shape[2..].iter().any(|&n| n != 1) {
+            return Err(Error::Shape {
+                expected: "a 2D array".to_string(),
+                found: shape,
+            });
+        }
+        Ok((shape[0], shape[1]))
+    }
+    /// Removes the singleton dimensions like Matlab `squeeze`
+    ///
+    /// A 2D array is left unchanged and a squeezed vector is a Matlab column vector
+    pub fn squeeze(self) -> MatVar<T> {
+        if self.shape.len() <= 2 {
+            return self;
+        }
+        let mut shape: Vec<usize> = self.shape.iter().copied().filter(|&n| n != 1).collect();
+        shape.reverse();
+        shape.resize(shape.len().max(2), 1);
+        shape.reverse();
+        MatVar { shape, ..self }
+    }
+    /// Returns the number of rows, 1 if the HDF5 dataset has less than 2 dimensions
     pub fn n_row(&self) -> usize {
-        self.shape[1]
+        self.shape.get(1).copied().unwrap_or(1)
     }
+    /// Returns the number of columns, 1 if the HDF5 dataset is a scalar
     pub fn n_column(&self) -> usize {
-        self.shape[0]
+        self.shape.first().copied().unwrap_or(1)
+    }
+    /// Changes the Matlab shape of the array like Matlab `reshape`, leaving the data untouched
+    ///
+    /// The new Matlab shape must have the same number of elements
+    pub fn reshape(self, new_shape: Vec<usize>) -> Result<MatVar<T>> {
+        if checked_product(&new_shape)? == self.data.len() {
+            Ok(MatVar {
+                shape: new_shape.into_iter().rev().collect(),
+                ..self
+            })
+        } else {
+            Err(Error::Shape {
+                expected: format!("{} elements", self.data.len()),
+                found: new_shape,
+            })
+        }
+    }
+    /// Returns a transposed view of a Matlab 2D array, without reordering the elements
+    ///
+    /// Trailing singleton dimensions are ignored, returns [Error::Shape] for other arrays
+    pub fn t(&self) -> Result<TransposedView<'_, T>> {
+        let (n_row, n_column) = self.dims()?;
+        Ok(TransposedView {
+            data: &self.data,
+            n_row: n_column,
+            n_column: n_row,
+        })
+    }
+    /// Concatenates 2 arrays along the Matlab dimension `axis`, 0 for the rows and 1 for the columns,
+    /// like Matlab `cat(axis+1, A, B)`
+    ///
+    /// The other dimensions must be the same, returns [Error::Shape] otherwise
+    pub fn concat(self, other: MatVar<T>, axis: usize) -> Result<MatVar<T>> {
+        let mut shape = self.matlab_shape();
+        let mut other_shape = other.matlab_shape();
+        let n = shape.len().max(other_shape.len()).max(axis + 1);
+        shape.resize(n, 1);
+        other_shape.resize(n, 1);
+        if (0..n).any(|k| k != axis && shape[k] != other_shape[k]) {
+            return Err(Error::Shape {
+                expected: format!("Matlab shape {:?} but along dimension {}", shape, axis),
+                found: other_shape,
+            });
+        }
+        // the data is a sequence of blocks of all the dimensions up to `axis`
+        let block = checked_product(&shape[..=axis])?;
+        let other_block = checked_product(&other_shape[..=axis])?;
+        let n_block = checked_product(&shape[axis + 1..])?;
+        let mut data = Vec::with_capacity(self.data.len() + other.data.len());
+        let (mut lhs, mut rhs) = (self.data.into_iter(), other.data.into_iter());
+        for _ in 0..n_block {
+            data.extend(lhs.by_ref().take(block));
+            data.extend(rhs.by_ref().take(other_block));
+        }
+        shape[axis] += other_shape[axis];
+        shape.reverse();
+        Ok(MatVar {
+            name: self.name,
+            shape,
+            data,
+        })
+    }
+    /// Creates a ndarray 1D array from a Matlab row or column vector
+    ///
+    /// Returns [Error::Shape] if the array is not a vector
+    #[cfg(feature = "ndarray")]
+    pub fn into_array1(self) -> Result<ndarray::Array1<T>> {
+        check_vector(&self.shape)?;
+        Ok(ndarray::Array1::from_vec(self.data))
+    }
+}
+/// Checks that an array is a row or column vector: no more than one dimension of the HDF5 `shape` is greater than 1
+fn check_vector(shape: &[usize]) -> Result<()> {
+    if shape.iter().filter(|&&n| n > 1).count() > 1 {
+        Err(Error::Shape {
+            expected: "a vector".to_string(),
+            found: matlab_shape(shape),
+        })
+    } else {
+        Ok(())
+    }
+}
+impl<T: Clone> MatVar<T> {
+    fn check_2d(&self) -> Result<()> {
+        if self.shape.len() > 2 {
+            Err(Error::Shape {
+                expected: "a 2D array".to_string(),
+                found: self.matlab_shape(),
+            })
+        } else {
+            Ok(())
+        }
+    }
+    /// Returns the diagonal of a Matlab square matrix
+    pub fn diagonal(&self) -> Result<Vec<T>> {
+        self.check_2d()?;
+        let n = self.n_row();
+        if n != self.n_column() {
+            return Err(Error::Shape {
+                expected: "a square matrix".to_string(),
+                found: self.matlab_shape(),
+            });
+        }
+        Ok(self.data.iter().step_by(n + 1).cloned().collect())
+    }
+    /// Returns the block of a Matlab matrix at the given rows and columns
+    pub fn submatrix(
+        &self,
+        rows: std::ops::Range<usize>,
+        cols: std::ops::Range<usize>,
+    ) -> Result<MatVar<T>> {
+        self.check_2d()?;
+        let n_row = self.n_row();
+        if rows.start > rows.end
+            || rows.end > n_row
+            || cols.start > cols.end
+            || cols.end > self.n_column()
+        {
+            return Err(Error::Shape {
+                expected: format!("rows {:?} and columns {:?} within the matrix", rows, cols),
+                found: self.matlab_shape(),
+            });
+        }
+        Ok(MatVar {
+            name: self.name.clone(),
+            shape: vec![cols.len(), rows.len()],
+            data: cols
+                .flat_map(|j| self.data[j * n_row + rows.start..j * n_row + rows.end].iter())
+                .cloned()
+                .collect(),
+        })
+    }
+    /// Reorders the Matlab dimensions like Matlab `permute`, with the 0-based dimension indices `perm`
+    ///
+    /// The Matlab dimension `k` of the permuted array is the dimension `perm[k]` of the array,
+    /// returns [Error::Shape] if `perm` is not a permutation of the Matlab dimensions
+    pub fn with_permutation(self, perm: &[usize]) -> Result<MatVar<T>> {
+        let shape = self.matlab_shape();
+        let mut sorted = perm.to_vec();
+        sorted.sort_unstable();
+        if sorted != (0..shape.len()).collect::<Vec<usize>>() {
+            return Err(Error::Shape {
+                expected: format!(
+                    "a permutation of {} dimensions, not {:?}",
+                    shape.len(),
+                    perm
+                ),
+                found: shape,
+            });
+        }
+        let strides = column_major_strides(&shape)?;
+        let new_shape: Vec<usize> = perm.iter().map(|&k| shape[k]).collect();
+        // column-major multi-index of the permuted array
+        let mut index = vec![0; new_shape.len()];
+        let data = (0..self.data.len())
+            .map(|_| {
+                let offset: usize = index.iter().zip(perm).map(|(&i, &k)| i * strides[k]).sum();
+                for (i, &n) in index.iter_mut().zip(&new_shape) {
+                    *i += 1;
+                    if *i < n {
+                        break;
+                    }
+                    *i = 0;
+                }
+                self.data[offset].clone()
+            })
+            .collect();
+        Ok(MatVar {
+            name: self.name,
+            shape: new_shape.into_iter().rev().collect(),
+            data,
+        })
+    }
+    /// Returns the nonzero elements keyed by their column-major linear index, zero being `T::default()`
+    pub fn nonzero_map(&self) -> std::collections::BTreeMap<usize, T>
+    where
+        T: PartialEq + Default,
+    {
+        let zero = T::default();
+        self.data
+            .iter()
+            .enumerate()
+            .filter(|(_, x)| **x != zero)
+            .map(|(k, x)| (k, x.clone()))
+            .collect()
+    }
+    /// Converts a Matlab `R`x`C` matrix into an array of `R` rows
+    pub fn into_array_2d<const R: usize, const C: usize>(self) -> Result<[[T; C]; R]> {
+        self.check_2d()?;
+        if self.n_row() != R || self.n_column() != C {
+            return Err(Error::Shape {
+                expected: format!("a {}x{} matrix", R, C),
+                found: self.matlab_shape(),
+            });
+        }
+        Ok(std::array::from_fn(|i| {
+            std::array::from_fn(|j| self.data[j * R + i].clone())
+        }))
+    }
+}
+impl MatVar<f64> {
+    /// Returns the elements in Matlab linear index order, mapping NaN to `None`
+    ///
+    /// Infinite values are kept
+    pub fn into_option_vec(self) -> Vec<Option<f64>> {
+        self.data
+            .into_iter()
+            .map(|x| if x.is_nan() { None } else { Some(x) })
+            .collect()
+    }
+    /// Returns the column-major elements of a Matlab 2D array with its number of rows and columns,
+    /// as expected by BLAS and LAPACK
+    ///
+    /// The Matlab element `A(i+1, j+1)` is `data[i + j * n_row]`, the leading dimension being the number of rows;
+    /// trailing singleton dimensions are ignored, returns [Error::Shape] for other arrays
+    pub fn as_blas(self) -> Result<(Vec<f64>, usize, usize)> {
+        let (n_row, n_column) = self.dims()?;
+        Ok((self.data, n_row, n_column))
+    }
+}
+/// Creates an empty, unnamed, Matlab array
+impl<T> Default for MatVar<T> {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            shape: vec![0, 0],
+            data: Vec::new(),
+        }
+    }
+}
+/// Transposed view of a Matlab 2D array, see [MatVar::t]
+#[derive(Debug, Clone, Copy)]
+pub struct TransposedView<'a, T> {
+    data: &'a [T],
+    n_row: usize,
+    n_column: usize,
+}
+impl<'a, T> TransposedView<'a, T> {
+    /// Returns the number of rows and columns of the transposed array
+    pub fn dims(&self) -> (usize, usize) {
+        (self.n_row, self.n_column)
+    }
+    /// Returns the element `(i, j)` of the transposed array, i.e. the element `(j, i)` of the array
+    pub fn get(&self, i: usize, j: usize) -> Option<&'a T> {
+        if i < self.n_row && j < self.n_column {
+            self.data.get(j + i * self.n_column)
+        } else {
+            None
+        }
+    }
+    /// Iterates over the row `i` of the transposed array, i.e. the column `i` of the array
+    pub fn row(&self, i: usize) -> impl Iterator<Item = &'a T> {
+        let n = if i < self.n_row { self.n_column } else { 0 };
+        self.data.iter().skip(i * self.n_column).take(n)
+    }
+    /// Iterates over the column `j` of the transposed array, i.e. the row `j` of the array
+    pub fn column(&self, j: usize) -> impl Iterator<Item = &'a T> {
+        let n = if j < self.n_column { self.n_row } else { 0 };
+        self.data
+            .iter()
+            .skip(j)
+            .step_by(self.n_column.max(1))
+            .take(n)
+    }
+}
+impl<'a, T> std::ops::Index<(usize, usize)> for TransposedView<'a, T> {
+    type Output = T;
+    fn index(&self, (i, j): (usize, usize)) -> &T {
+        match self.get(i, j) {
+            Some(x) => x,
+            None => panic!(
+                "index ({}, {}) out of bounds of the {}x{} transposed array",
+                i, j, self.n_row, self.n_column
+            ),
+        }
     }
 }
 /// Matlab struct
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct MatStruct {
+    /// Field names in the declaration order of the `MATLAB_fields` attribute
+    ///
+    /// The names are never taken from the HDF5 group members, whose order is undefined:
+    /// a struct without `MATLAB_fields` attribute has no field names
     pub field_names: Vec<String>,
+    len: usize,
+    group: Option<hdf5::Group>,
+}
+/// Expected Matlab class and shape of a variable, see [File::validate]
+#[derive(Debug, Clone, PartialEq)]
+pub struct VarSpec {
+    pub name: String,
+    pub class: String,
+    /// Matlab shape, not checked if `None`
+    pub shape: Option<Vec<usize>>,
+}
+impl VarSpec {
+    pub fn new(name: &str, class: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            class: class.to_string(),
+            shape: None,
+        }
+    }
+    pub fn shape(self, shape: &[usize]) -> Self {
+        Self {
+            shape: Some(shape.to_vec()),
+            ..self
+        }
+    }
+}
+/// Mismatch between a variable and its [VarSpec]
+#[derive(Debug, Clone, PartialEq)]
+pub enum SchemaError {
+    Missing(String),
+    /// The class or the shape of the variable cannot be read
+    Unreadable {
+        name: String,
+        error: String,
+    },
+    Class {
+        name: String,
+        expected: String,
+        found: String,
+    },
+    Shape {
+        name: String,
+        expected: Vec<usize>,
+        found: Vec<usize>,
+    },
+}
+impl std::fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SchemaError::Missing(name) => write!(f, "Matlab variable {} is missing", name),
+            SchemaError::Unreadable { name, error } => {
+                write!(f, "Matlab variable {} cannot be read: {}", name, error)
+            }
+            SchemaError::Class {
+                name,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Matlab class of {} is {}, expected {}",
+                name, found, expected
+            ),
+            SchemaError::Shape {
+                name,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Matlab shape of {} is {:?}, expected {:?}",
+                name, found, expected
+            ),
+        }
+    }
 }
 
-/// Matlab 7.3 mat file
-pub struct File {
-    h5: hdf5::File,
+/// Matlab struct field metadata
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldInfo {
+    pub class: String,
+    pub shape: Vec<usize>,
+    /// HDF5 element type, `None` for a field stored as a group (e.g. a nested struct)
+    pub dtype: Option<hdf5::types::TypeDescriptor>,
 }
-impl File {
-    /// Open a Matlab mat file
-    pub fn new<P: AsRef<std::path::Path>>(file: P) -> Result<Self> {
-        Ok(Self {
-            h5: hdf5::File::open(file)?,
-        })
+impl MatStruct {
+    /// Returns the HDF5 group holding the struct, checking that it has the field `name`
+    fn fields_group(&self, name: &str) -> Result<&hdf5::Group> {
+        match &self.group {
+            Some(group) if self.field_names.iter().any(|field| field == name) => Ok(group),
+            _ => Err(Error::Field(name.to_string())),
+        }
     }
-    /// Read a Matlab array
-    pub fn array<T: hdf5::H5Type>(&self, name: &str) -> Result<MatVar<T>> {
-        let dataset = match self.h5.dataset(name) {
+    /// Read a struct field as a Matlab array
+    pub fn field<T: hdf5::H5Type>(&self, name: &str) -> Result<MatVar<T>> {
+        match self.fields_group(name)?.dataset(name) {
+            Ok(dataset) => read_array(&dataset),
+            _ => Err(Error::Dataset(name.to_string())),
+        }
+    }
+    /// Read a 2D struct field as a nalgebra matrix, see [File::matrix]
+    #[cfg(feature = "nalgebra")]
+    pub fn matrix_field<T>(&self, name: &str) -> Result<nalgebra::DMatrix<T>>
+    where
+        T: hdf5::H5Type + 'static + std::marker::Copy + std::cmp::PartialEq + std::fmt::Debug,
+    {
+        self.field::<T>(name)?.into_matrix()
+    }
+    /// Read a 1x1 struct field
+    pub fn scalar_field<T: hdf5::H5Type>(&self, name: &str) -> Result<T> {
+        match self.fields_group(name)?.dataset(name) {
+            Ok(dataset) => scalar_value(&dataset),
+            _ => Err(Error::Dataset(name.to_string())),
+        }
+    }
+    /// Read a char struct field as a string, trimming the trailing padding spaces and nulls
+    ///
+    /// The rows of a char matrix are joined by newlines, see [File::text]
+    pub fn string_field(&self, name: &str) -> Result<String> {
+        let dataset = match self.fields_group(name)?.dataset(name) {
+            Ok(it) => it,
+            _ => return Err(Error::Dataset(name.to_string())),
+        };
+        check_class(&dataset, name, "char")?;
+        Ok(char_string(&dataset)?
+            .trim_end_matches([' ', '\0'])
+            .to_string())
+    }
+    /// Read a scalar field across all the elements of a struct array
+    ///
+    /// The returned array has the shape of the struct array
+    pub fn collect_field<T: hdf5::H5Type>(&self, name: &str) -> Result<MatVar<T>> {
+        let group = self.fields_group(name)?;
+        let dataset = match group.dataset(name) {
             Ok(it) => it,
             _ => return Err(Error::Dataset(name.to_string())),
         };
+        // the fields of a struct array are datasets of references without a Matlab class
+        let (shape, data) = if dataset.attr("MATLAB_class").is_ok() {
+            (vec![1, 1], vec![scalar_value(&dataset)?])
+        } else {
+            let data = dataset
+                .read_raw::<hdf5::ObjectReference1>()?
+                .iter()
+                .map(|reference| match group.dereference(reference)? {
+                    hdf5::ReferencedObject::Dataset(element) => scalar_value(&element),
+                    _ => Err(Error::Dataset(name.to_string())),
+                })
+                .collect::<Result<Vec<T>>>()?;
+            (dataset.shape(), data)
+        };
         Ok(MatVar {
             name: dataset.name(),
-            shape: dataset.shape(),
-            data: dataset.read_raw::<T>()?,
+            shape,
+            data,
         })
     }
-    pub fn structure(&self, name: &str) -> Result<MatStruct> {
-        let group = match self.h5.group(name) {
-            Ok(it) => it,
-            _ => return Err(Error::Group(name.to_string())),
-        };
-        group
-            .attr("MATLAB_class")?
-            .read_scalar::<hdf5::types::FixedAscii<256>>()
-            .and_then(|matlab_type| {
-                if matlab_type.as_str() == "struct" {
-                    Ok(())
-                } else {
-                    Err("Matlab class is not a struct".into())
-                }
-            })?;
-        let field_names = group.attr("MATLAB_fields")?.read_raw().map(
-            |data: Vec<hdf5::types::VarLenArray<FixedAscii<1>>>| {
-                data.into_iter()
-                    .map(|v| v.iter().map(|x| x.as_str()).collect::<String>())
-                    .collect::<Vec<String>>()
+    /// Read a struct field that is itself a struct
+    pub fn substruct(&self, name: &str) -> Result<MatStruct> {
+        open_struct(self.fields_group(name)?, name)
+    }
+    /// Read only the given struct fields as Matlab arrays
+    pub fn read_fields<T: hdf5::H5Type>(&self, names: &[&str]) -> Result<Vec<(String, MatVar<T>)>> {
+        names
+            .iter()
+            .map(|&name| Ok((name.to_string(), self.field(name)?)))
+            .collect()
+    }
+    /// Read all the fields of a struct of equal-length arrays as columns, in [MatStruct::field_names] order
+    ///
+    /// Returns [Error::Shape] if a field length differs from the length of the first field
+    pub fn into_columns<T: hdf5::H5Type>(&self) -> Result<Vec<(String, MatVar<T>)>> {
+        let mut columns: Vec<(String, MatVar<T>)> = Vec::with_capacity(self.field_names.len());
+        for name in &self.field_names {
+            let column = self.field::<T>(name)?;
+            if let Some((first, first_column)) = columns.first() {
+                if column.data.len() != first_column.data.len() {
+                    return Err(Error::Shape {
+                        expected: format!(
+                            "{} elements like field `{}`",
+                            first_column.data.len(),
+                            first
+                        ),
+                        found: column.matlab_shape(),
+                    });
+                }
+            }
+            columns.push((name.clone(), column));
+        }
+        Ok(columns)
+    }
+    /// Returns the Matlab class, shape and HDF5 type of a field without reading its data
+    ///
+    /// The shape of a field stored as a group is empty
+    pub fn field_info(&self, name: &str) -> Result<FieldInfo> {
+        let group = self.fields_group(name)?;
+        if let Ok(dataset) = group.dataset(name) {
+            Ok(FieldInfo {
+                class: matlab_class(&dataset)?,
+                shape: dataset.shape(),
+                dtype: Some(dataset.dtype()?.to_descriptor()?),
+            })
+        } else {
+            let group = match group.group(name) {
+                Ok(it) => it,
+                _ => return Err(Error::Group(name.to_string())),
+            };
+            Ok(FieldInfo {
+                class: matlab_class(&group)?,
+                shape: Vec::new(),
+                dtype: None,
+            })
+        }
+    }
+    /// Returns the value of the field `name` if it is an empty Matlab array
+    fn empty_field(&self, name: &str, class: &str) -> Result<Option<MatValue>> {
+        match self.fields_group(name)?.dataset(name) {
+            Ok(dataset) => empty_value(&dataset, class),
+            _ => Ok(None),
+        }
+    }
+    /// Read a struct field according to its Matlab class, as [File::read] reads variables
+    fn field_value(&self, name: &str) -> Result<MatValue> {
+        let info = self.field_info(name)?;
+        if let Some(value) = self.empty_field(name, &info.class)? {
+            return Ok(value);
+        }
+        Ok(match info.class.as_str() {
+            class if NUMERIC_CLASSES.contains(&class) => MatValue::Numeric(self.field(name)?),
+            "logical" => {
+                let var = self.field::<u8>(name)?;
+                MatValue::Logical(MatVar {
+                    name: var.name,
+                    shape: var.shape,
+                    data: var.data.into_iter().map(|x| x != 0).collect(),
+                })
+            }
+            "char" => MatValue::Char(self.string_field(name)?),
+            "struct" => MatValue::Struct(self.substruct(name)?),
+            _ => MatValue::Unsupported {
+                class: info.class,
+                shape: info.shape,
             },
-        )?;
-        Ok(MatStruct { field_names })
+        })
+    }
+    /// Reads all the fields of the struct and of its nested structs, keyed by their dotted path like `solver.tol`
+    ///
+    /// The fields are read as [File::read] reads variables
+    pub fn flatten(&self) -> Result<HashMap<String, MatValue>> {
+        let mut values = HashMap::new();
+        for name in &self.field_names {
+            match self.field_value(name)? {
+                MatValue::Struct(substruct) => {
+                    for (key, value) in substruct.flatten()? {
+                        values.insert(format!("{}.{}", name, key), value);
+                    }
+                }
+                value => {
+                    values.insert(name.clone(), value);
+                }
+            }
+        }
+        Ok(values)
+    }
+    /// Reads each field of the struct independently, returning the result of each read
+    ///
+    /// The fields are read as [File::read] reads variables, a field that cannot be read does not prevent
+    /// reading the others
+    pub fn read_all_fields(&self) -> Vec<(String, Result<MatValue>)> {
+        self.field_names
+            .iter()
+            .map(|name| (name.clone(), self.field_value(name)))
+            .collect()
+    }
+    /// Returns the number of elements of the struct array, 0 for an empty struct
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
     }
 }
 
-/// Creates a Rust `Vec` from a Matlab array, column wise
-impl<T> From<MatVar<T>> for Vec<T> {
-    fn from(var: MatVar<T>) -> Self {
-        var.raw()
+/// Matlab variable metadata, see [File::manifest]
+#[derive(Debug, Clone, PartialEq)]
+pub struct VarInfo {
+    pub name: String,
+    /// HDF5 path
+    pub path: String,
+    pub class: String,
+    /// Matlab shape, empty for a variable stored as a group (e.g. a struct)
+    pub shape: Vec<usize>,
+    /// HDF5 element type, `None` for a variable stored as a group
+    pub dtype: Option<hdf5::types::TypeDescriptor>,
+    /// Size of the data in memory, see [File::size_bytes], 0 for a variable stored as a group
+    pub size_bytes: usize,
+}
+
+/// Kind of a HDF5 node, see [File::children]
+#[derive(Debug, Clone, PartialEq)]
+pub enum NodeKind {
+    /// HDF5 dataset with its Matlab class, if any
+    Dataset(Option<String>),
+    /// HDF5 group with its Matlab class, if any
+    Group(Option<String>),
+}
+
+/// Content of the `#subsystem#` group of a mat file, see [File::subsystem_info]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SubsystemInfo {
+    /// Members of the `#subsystem#` group, e.g. `MCOS` for Matlab objects
+    pub members: Vec<String>,
+    /// Matlab classes of the top-level object variables
+    pub classes: Vec<String>,
+}
+
+/// Matlab sparse matrix, in compressed sparse column format
+#[derive(Debug)]
+pub struct MatSparse<T> {
+    name: String,
+    n_row: usize,
+    column_pointers: Vec<usize>,
+    row_indices: Vec<usize>,
+    values: Vec<T>,
+}
+impl<T> MatSparse<T> {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn n_row(&self) -> usize {
+        self.n_row
+    }
+    pub fn n_column(&self) -> usize {
+        self.column_pointers.len().saturating_sub(1)
+    }
+    /// Returns the number of stored elements
+    pub fn nnz(&self) -> usize {
+        self.values.len()
+    }
+    /// Returns the index of the first stored element of each column, followed by the number of stored elements
+    pub fn column_pointers(&self) -> &[usize] {
+        &self.column_pointers
+    }
+    /// Returns the 0-based row index of each stored element
+    pub fn row_indices(&self) -> &[usize] {
+        &self.row_indices
+    }
+    /// Returns the stored elements, column wise
+    pub fn values(&self) -> &[T] {
+        &self.values
+    }
+    /// Iterates over the 0-based `(row, column, value)` triplets of the stored elements
+    pub fn triplets(&self) -> impl Iterator<Item = (usize, usize, &T)> {
+        self.column_pointers
+            .windows(2)
+            .enumerate()
+            .flat_map(|(j, bounds)| (bounds[0]..bounds[1]).map(move |k| (j, k)))
+            .map(move |(j, k)| (self.row_indices[k], j, &self.values[k]))
     }
 }
-#[cfg(feature = "nalgebra")]
-/// Creates a nalgebra matrix from a Matlab 2D array
-impl<T: 'static + std::marker::Copy + std::cmp::PartialEq + std::fmt::Debug> From<MatVar<T>>
-    for nalgebra::Matrix<
-        T,
-        nalgebra::Dynamic,
-        nalgebra::Dynamic,
-        nalgebra::VecStorage<T, nalgebra::Dynamic, nalgebra::Dynamic>,
-    >
-{
-    fn from(var: MatVar<T>) -> Self {
-        let shape = var.shape();
-        if shape.len() > 2 {
-            unimplemented!("Matlab array dimension cannot be greater than 2")
-        } else {
-            nalgebra::DMatrix::from_column_slice(shape[1], shape[0], &var.raw())
+
+/// Matlab variable of any supported class
+#[derive(Debug)]
+pub enum MatValue {
+    /// Numeric array of any Matlab numeric class, read as `f64`
+    Numeric(MatVar<f64>),
+    Logical(MatVar<bool>),
+    Char(String),
+    Struct(MatStruct),
+    /// Variable of a Matlab class that cannot be read, e.g. a function handle
+    Unsupported {
+        class: String,
+        shape: Vec<usize>,
+    },
+}
+
+/// Matlab numeric array in its stored element type, see [File::read_numeric]
+#[derive(Debug)]
+pub enum NumericArray {
+    F64(MatVar<f64>),
+    F32(MatVar<f32>),
+    I64(MatVar<i64>),
+    I32(MatVar<i32>),
+    I16(MatVar<i16>),
+    I8(MatVar<i8>),
+    U64(MatVar<u64>),
+    U32(MatVar<u32>),
+    U16(MatVar<u16>),
+    U8(MatVar<u8>),
+}
+/// Matlab class of the arrays read by [File::read_tagged]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MatType {
+    Double,
+    Single,
+    Int8,
+    Uint8,
+    Int16,
+    Uint16,
+    Int32,
+    Uint32,
+    Int64,
+    Uint64,
+    Logical,
+    Char,
+}
+impl MatType {
+    const ALL: [MatType; 12] = [
+        MatType::Double,
+        MatType::Single,
+        MatType::Int8,
+        MatType::Uint8,
+        MatType::Int16,
+        MatType::Uint16,
+        MatType::Int32,
+        MatType::Uint32,
+        MatType::Int64,
+        MatType::Uint64,
+        MatType::Logical,
+        MatType::Char,
+    ];
+    /// Returns the type of the Matlab class name, `None` for the other classes
+    pub fn from_class(class: &str) -> Option<MatType> {
+        Self::ALL.iter().copied().find(|t| t.class() == class)
+    }
+    /// Returns the Matlab class name
+    pub fn class(&self) -> &'static str {
+        match self {
+            MatType::Double => "double",
+            MatType::Single => "single",
+            MatType::Int8 => "int8",
+            MatType::Uint8 => "uint8",
+            MatType::Int16 => "int16",
+            MatType::Uint16 => "uint16",
+            MatType::Int32 => "int32",
+            MatType::Uint32 => "uint32",
+            MatType::Int64 => "int64",
+            MatType::Uint64 => "uint64",
+            MatType::Logical => "logical",
+            MatType::Char => "char",
         }
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    #[test]
-    fn array_to_vec() {
-        let file = "examples/arrays.mat";
-        let mat_file = File::new(file).unwrap();
-        let var: Vec<f64> = mat_file.array("q").unwrap().into();
-        assert_eq!(var, vec![1.0, 4.0, 2.0, 5.0, 3.0, 6.0])
+/// Reader of the Matlab variables of a given class, see [File::with_handler]
+pub type ClassHandler = Box<dyn Fn(&hdf5::Group) -> Result<MatValue> + Send + Sync>;
+
+/// Matlab classes of the numeric arrays
+const NUMERIC_CLASSES: [&str; 10] = [
+    "double", "single", "int8", "uint8", "int16", "uint16", "int32", "uint32", "int64", "uint64",
+];
+/// Largest number of elements of the arrays [File::summarize] computes the statistics of
+const SUMMARY_ELEMENTS: usize = 1 << 16;
+
+/// Matlab 7.3 mat file
+pub struct File {
+    h5: hdf5::File,
+    handlers: HashMap<String, ClassHandler>,
+}
+impl File {
+    fn from_hdf5(h5: hdf5::File) -> Self {
+        Self {
+            h5,
+            handlers: HashMap::new(),
+        }
     }
-    #[test]
-    fn array_to_matrix() {
-        let file = "examples/arrays.mat";
-        let mat_file = File::new(file).unwrap();
-        let var: nalgebra::DMatrix<f64> = mat_file.array("w").unwrap().into();
-        assert_eq!(
-            var,
-            nalgebra::DMatrix::from_column_slice(3, 2, &[0., -6., -2., 1., 56., 1.])
-        )
+    /// Open a Matlab mat file
+    pub fn new<P: AsRef<std::path::Path>>(file: P) -> Result<Self> {
+        Ok(Self::from_hdf5(hdf5::File::open(file)?))
+    }
+    /// Open a Matlab mat file with the HDF5 library version bounds of the objects created when accessing the file
+    ///
+    /// If the file cannot be opened because its HDF5 superblock is newer than the HDF5 library,
+    /// the error is [Error::Superblock]
+    pub fn with_libver_bounds<P: AsRef<std::path::Path>>(
+        file: P,
+        low: hdf5::plist::LibraryVersion,
+        high: hdf5::plist::LibraryVersion,
+    ) -> Result<Self> {
+        let fapl = hdf5::plist::FileAccessBuilder::new()
+            .libver_bounds(low, high)
+            .finish()?;
+        match hdf5::File::with_options()
+            .set_access_plist(&fapl)?
+            .open(file.as_ref())
+        {
+            Ok(h5) => Ok(Self::from_hdf5(h5)),
+            Err(e) => match superblock_version(file.as_ref()) {
+                Some(version) if version > max_superblock_version() => Err(Error::Superblock {
+                    file: file.as_ref().display().to_string(),
+                    version,
+                }),
+                _ => Err(e.into()),
+            },
+        }
+    }
+    /// Registers a reader for the Matlab variables stored as HDF5 groups with the given `MATLAB_class`
+    ///
+    /// The handler is used by [File::read] and [File::iter] instead of the built-in readers;
+    /// a variable of that class stored as a HDF5 dataset is still read by the built-in readers
+    pub fn with_handler(mut self, class_name: &str, handler: ClassHandler) -> Self {
+        self.handlers.insert(class_name.to_string(), handler);
+        self
+    }
+    /// Open a Matlab mat file from its content in memory
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let fapl = hdf5::plist::FileAccessBuilder::new()
+            .core_filebacked(false)
+            .finish()?;
+        // HDF5 makes its own copy of the file image
+        if hdf5::sync::sync(|| unsafe {
+            hdf5_sys::h5p::H5Pset_file_image(fapl.id(), bytes.as_ptr() as *mut _, bytes.len())
+        }) < 0
+        {
+            return Err(hdf5::Error::from("Setting the HDF5 file image failed").into());
+        }
+        Ok(Self::from_hdf5(
+            hdf5::File::with_options()
+                .set_access_plist(&fapl)?
+                .open("memory.mat")?,
+        ))
+    }
+    /// Open a gzip or zstd compressed Matlab mat file
+    ///
+    /// The file is decompressed in memory, the compression is detected from the file magic bytes
+    /// and an uncompressed file is opened as is
+    #[cfg(feature = "compressed")]
+    pub fn from_compressed<P: AsRef<std::path::Path>>(file: P) -> Result<Self> {
+        use std::io::Read;
+        let bytes = std::fs::read(file)?;
+        match bytes.get(..4) {
+            Some([0x1f, 0x8b, _, _]) => {
+                let mut buffer = Vec::new();
+                flate2::read::GzDecoder::new(bytes.as_slice()).read_to_end(&mut buffer)?;
+                Self::from_bytes(&buffer)
+            }
+            Some([0x28, 0xb5, 0x2f, 0xfd]) => {
+                Self::from_bytes(&zstd::stream::decode_all(bytes.as_slice())?)
+            }
+            _ => Self::from_bytes(&bytes),
+        }
+    }
+    /// Returns the underlying HDF5 file
+    ///
+    /// Reading the file with the HDF5 API bypasses the Matlab specific logic of this crate
+    pub fn hdf5(&self) -> &hdf5::File {
+        &self.h5
+    }
+    /// Converts into the underlying HDF5 file
+    pub fn into_hdf5(self) -> hdf5::File {
+        self.h5
+    }
+    fn dataset(&self, name: &str) -> Result<hdf5::Dataset> {
+        match self.h5.dataset(name) {
+            Ok(it) => Ok(it),
+            _ => match external_link(&self.h5, name) {
+                Some((file, object)) => self.external_dataset(name, &file, &object),
+                None => Err(Error::Dataset(name.to_string())),
+            },
+        }
+    }
+    /// Opens the `object` dataset of an external `file`, a relative path being resolved from the directory of this file
+    fn external_dataset(&self, name: &str, file: &str, object: &str) -> Result<hdf5::Dataset> {
+        let path = match std::path::Path::new(&self.h5.filename()).parent() {
+            Some(directory) => directory.join(file),
+            None => std::path::PathBuf::from(file),
+        };
+        if !path.exists() {
+            return Err(Error::ExternalLink {
+                name: name.to_string(),
+                file: path.display().to_string(),
+            });
+        }
+        match hdf5::File::open(&path)?.dataset(object) {
+            Ok(it) => Ok(it),
+            _ => Err(Error::Dataset(name.to_string())),
+        }
+    }
+    fn group(&self, name: &str) -> Result<hdf5::Group> {
+        match self.h5.group(name) {
+            Ok(it) => Ok(it),
+            _ => Err(Error::Group(name.to_string())),
+        }
+    }
+    /// Read a Matlab array
+    ///
+    /// If the variable is a sparse matrix or a struct, the error names the method to read it with
+    ///
+    /// A group without Matlab class wrapping a `value` dataset or a single dataset is read as that dataset,
+    /// a Matlab object with a `MATLAB_object_decode` attribute returns [Error::Object]
+    pub fn array<T: hdf5::H5Type>(&self, name: &str) -> Result<MatVar<T>> {
+        match self.h5.group(name) {
+            Ok(group) => match wrapped_dataset(&group) {
+                Some(dataset) => read_array(&dataset),
+                None => Err(wrong_reader(&group, name)),
+            },
+            _ => {
+                let dataset = self.dataset(name)?;
+                // the data of an object dataset are the references to its serialization in `#subsystem#`
+                match object_error(&dataset, name) {
+                    Some(e) => Err(e),
+                    None => read_array(&dataset),
+                }
+            }
+        }
+    }
+    /// Read a Matlab array of the `#refs#` group, where Matlab stores the elements of cells and struct arrays,
+    /// by its name in the group
+    ///
+    /// Same as [File::array] with the path `#refs#/ref_name`
+    pub fn ref_array<T: hdf5::H5Type>(&self, ref_name: &str) -> Result<MatVar<T>> {
+        self.array(&format!("#refs#/{}", ref_name))
+    }
+    /// Read a Matlab indexed image as an RGB image, mapping the elements of the 2D array `index_name`
+    /// through the rows of the Nx3 colormap `colormap_name`
+    ///
+    /// Like Matlab, floating point indices start at 1 and integer indices at 0, the colormap values are in `[0, 1]`;
+    /// an element that is not an index of the colormap returns [Error::ColormapIndex]
+    #[cfg(feature = "image")]
+    pub fn indexed_image(&self, index_name: &str, colormap_name: &str) -> Result<image::RgbImage> {
+        use std::convert::TryFrom;
+        let dataset = self.dataset(index_name)?;
+        let first = match matlab_class(&dataset)?.as_str() {
+            "double" | "single" => 1.,
+            _ => 0.,
+        };
+        let index = read_array::<f64>(&dataset)?;
+        let (height, width) = index.dims()?;
+        let colormap = self.array::<f64>(colormap_name)?;
+        let n_color = match colormap.dims()? {
+            (n_color, 3) => n_color,
+            _ => {
+                return Err(Error::Shape {
+                    expected: "a Nx3 colormap".to_string(),
+                    found: colormap.matlab_shape(),
+                })
+            }
+        };
+        let too_large = || Error::Shape {
+            expected: format!("at most {} rows and columns", u32::MAX),
+            found: vec![height, width],
+        };
+        let (w, h) = (
+            u32::try_from(width).map_err(|_| too_large())?,
+            u32::try_from(height).map_err(|_| too_large())?,
+        );
+        let mut pixels = Vec::with_capacity(3 * index.data.len());
+        // the pixels are row-major and the Matlab arrays column-major
+        for i in 0..height {
+            for j in 0..width {
+                let k = i + j * height;
+                let value = index.data[k];
+                let color = value - first;
+                if color.fract() != 0. || !(0. ..n_color as f64).contains(&color) {
+                    return Err(Error::ColormapIndex {
+                        name: index_name.to_string(),
+                        index: k,
+                        value,
+                        n_color,
+                    });
+                }
+                let color = color as usize;
+                pixels.extend((0..3).map(|c| {
+                    (colormap.data[color + c * n_color].clamp(0., 1.) * 255.).round() as u8
+                }));
+            }
+        }
+        image::RgbImage::from_raw(w, h, pixels).ok_or_else(too_large)
+    }
+    /// Read a Matlab 2D array as a nalgebra matrix
+    ///
+    /// The stored elements are converted to `T` by HDF5, e.g. from a single precision or integer array
+    #[cfg(feature = "nalgebra")]
+    pub fn matrix<T>(&self, name: &str) -> Result<nalgebra::DMatrix<T>>
+    where
+        T: hdf5::H5Type + 'static + std::marker::Copy + std::cmp::PartialEq + std::fmt::Debug,
+    {
+        self.array::<T>(name)?.into_matrix()
+    }
+    /// Read all the top-level real numeric 2D arrays as nalgebra matrices of double precision elements
+    ///
+    /// The other variables, including the empty and the sparse arrays, are skipped
+    #[cfg(feature = "nalgebra")]
+    pub fn all_matrices(&self) -> Result<Vec<(String, nalgebra::DMatrix<f64>)>> {
+        let mut matrices = Vec::new();
+        for name in self.variables()? {
+            if self.h5.group(&name).is_ok() {
+                continue;
+            }
+            let dataset = self.dataset(&name)?;
+            let is_numeric =
+                matlab_class(&dataset).is_ok_and(|class| NUMERIC_CLASSES.contains(&class.as_str()));
+            if !is_numeric
+                || is_matlab_empty(&dataset)
+                || is_complex(&dataset.dtype()?.to_descriptor()?)
+            {
+                continue;
+            }
+            let var = read_array::<f64>(&dataset)?;
+            if let Ok((n_row, n_column)) = var.dims() {
+                let matrix = nalgebra::DMatrix::from_vec(n_row, n_column, var.data);
+                matrices.push((name, matrix));
+            }
+        }
+        Ok(matrices)
+    }
+    /// Returns a reader of the Matlab array `name` as double precision elements, to be configured before reading it
+    pub fn read_builder(&self, name: &str) -> ReadBuilder<'_, f64> {
+        ReadBuilder {
+            file: self,
+            name: name.to_string(),
+            orientation: Orientation::default(),
+            expected: None,
+            squeeze: false,
+            element: std::marker::PhantomData,
+        }
+    }
+    /// Read a real Matlab numeric array in its stored element type, without any conversion
+    pub fn read_numeric(&self, name: &str) -> Result<NumericArray> {
+        use hdf5::types::{FloatSize, IntSize, TypeDescriptor};
+        let dataset = self.dataset(name)?;
+        let descriptor = dataset.dtype()?.to_descriptor()?;
+        Ok(match descriptor {
+            TypeDescriptor::Float(FloatSize::U8) => NumericArray::F64(read_array(&dataset)?),
+            TypeDescriptor::Float(FloatSize::U4) => NumericArray::F32(read_array(&dataset)?),
+            TypeDescriptor::Integer(IntSize::U8) => NumericArray::I64(read_array(&dataset)?),
+            TypeDescriptor::Integer(IntSize::U4) => NumericArray::I32(read_array(&dataset)?),
+            TypeDescriptor::Integer(IntSize::U2) => NumericArray::I16(read_array(&dataset)?),
+            TypeDescriptor::Integer(IntSize::U1) => NumericArray::I8(read_array(&dataset)?),
+            TypeDescriptor::Unsigned(IntSize::U8) => NumericArray::U64(read_array(&dataset)?),
+            TypeDescriptor::Unsigned(IntSize::U4) => NumericArray::U32(read_array(&dataset)?),
+            TypeDescriptor::Unsigned(IntSize::U2) => NumericArray::U16(read_array(&dataset)?),
+            TypeDescriptor::Unsigned(IntSize::U1) => NumericArray::U8(read_array(&dataset)?),
+            _ => {
+                return Err(Error::Type {
+                    name: name.to_string(),
+                    expected: "a real numeric type".to_string(),
+                    found: descriptor.to_string(),
+                })
+            }
+        })
+    }
+    /// Read a real numeric, logical or char Matlab array in its stored element type with its Matlab class
+    ///
+    /// Logical arrays are stored as `u8` and char arrays as `u16` UTF-16 code units, see [File::read_numeric]
+    pub fn read_tagged(&self, name: &str) -> Result<(MatType, NumericArray)> {
+        let dataset = self.dataset(name)?;
+        let class = matlab_class(&dataset)?;
+        match MatType::from_class(&class) {
+            Some(mat_type) => Ok((mat_type, self.read_numeric(name)?)),
+            None => Err(Error::Class {
+                name: name.to_string(),
+                expected: "a numeric, logical or char class".to_string(),
+                found: class,
+            }),
+        }
+    }
+    /// Read a Matlab array block by block, calling `callback(bytes_read, total_bytes)` after each block
+    ///
+    /// The dataset is read along the first HDF5 dimension, a block being either a HDF5 chunk or about 1MB of data
+    pub fn array_with_progress<T: hdf5::H5Type>(
+        &self,
+        name: &str,
+        mut callback: impl FnMut(usize, usize),
+    ) -> Result<MatVar<T>> {
+        let dataset = self.dataset(name)?;
+        let shape = dataset.shape();
+        let n = element_count(&shape)?;
+        let total_bytes = checked_product(&[n, std::mem::size_of::<T>()])?;
+        let mut data: Vec<T> = Vec::with_capacity(n);
+        read_blocks(&dataset, |block| {
+            data.extend(block);
+            callback(data.len() * std::mem::size_of::<T>(), total_bytes);
+            Ok(())
+        })?;
+        Ok(MatVar {
+            name: dataset.name(),
+            shape,
+            data,
+        })
+    }
+    /// Read a Matlab 2D array in row-major order
+    ///
+    /// The blocks read from the dataset are written transposed into the returned array,
+    /// so its shape is the Matlab shape and [MatVar::n_row] and [MatVar::n_column] are swapped
+    pub fn array_row_major<T: hdf5::H5Type + Clone + Default>(
+        &self,
+        name: &str,
+    ) -> Result<MatVar<T>> {
+        let dataset = self.dataset(name)?;
+        let shape = dataset.shape();
+        let matlab_shape = matlab_shape(&shape);
+        if shape.len() > 2 {
+            return Err(Error::Shape {
+                expected: "a 2D array".to_string(),
+                found: matlab_shape,
+            });
+        }
+        let (n_row, n_column) = (matlab_shape[0], matlab_shape[1]);
+        let mut data = vec![T::default(); element_count(&shape)?];
+        // each HDF5 row of a block is a Matlab column
+        let mut column = 0;
+        read_blocks(&dataset, |block: Vec<T>| {
+            let n_block = block.len() / n_row.max(1);
+            for (k, x) in block.into_iter().enumerate() {
+                data[(k % n_row) * n_column + column + k / n_row] = x;
+            }
+            column += n_block;
+            Ok(())
+        })?;
+        Ok(MatVar {
+            name: dataset.name(),
+            shape: matlab_shape,
+            data,
+        })
+    }
+    /// Read a Matlab array in single precision, casting the blocks of double precision elements as they are read
+    ///
+    /// Only a block is held in double precision at a time, at the cost of the precision loss of the cast:
+    /// about 7 significant digits are kept and values beyond the `f32` range become infinite
+    pub fn array_downcast_f32(&self, name: &str) -> Result<MatVar<f32>> {
+        let dataset = self.dataset(name)?;
+        let shape = dataset.shape();
+        let mut data: Vec<f32> = Vec::with_capacity(element_count(&shape)?);
+        read_blocks(&dataset, |block: Vec<f64>| {
+            data.extend(block.into_iter().map(|x| x as f32));
+            Ok(())
+        })?;
+        Ok(MatVar {
+            name: dataset.name(),
+            shape,
+            data,
+        })
+    }
+    /// Checks if a Matlab array is complex, i.e. stored with a compound type of `real` and `imag` members
+    pub fn is_complex(&self, name: &str) -> Result<bool> {
+        Ok(is_complex(&self.dataset(name)?.dtype()?.to_descriptor()?))
+    }
+    /// Read a Matlab array one HDF5 chunk at a time
+    ///
+    /// Each chunk of a chunked dataset is read and decompressed on its own and copied into the array,
+    /// a dataset that is not chunked is read at once
+    pub fn array_chunked<T: hdf5::H5Type + Clone + Default>(
+        &self,
+        name: &str,
+    ) -> Result<MatVar<T>> {
+        let dataset = self.dataset(name)?;
+        let chunk = match dataset.chunk() {
+            Some(chunk) => chunk,
+            None => return read_array(&dataset),
+        };
+        let shape = dataset.shape();
+        let mut data = vec![T::default(); element_count(&shape)?];
+        if data.is_empty() {
+            return Ok(MatVar {
+                name: dataset.name(),
+                shape,
+                data,
+            });
+        }
+        // row-major strides of the HDF5 shape
+        let mut strides =
+            column_major_strides(&shape.iter().rev().copied().collect::<Vec<usize>>())?;
+        strides.reverse();
+        let n_chunk: Vec<usize> = shape
+            .iter()
+            .zip(&chunk)
+            .map(|(&n, &c)| n.div_ceil(c))
+            .collect();
+        // row-major multi-index of the chunk in the chunk grid
+        let mut index = vec![0; shape.len()];
+        loop {
+            let bounds: Vec<(usize, usize)> = index
+                .iter()
+                .zip(&chunk)
+                .zip(&shape)
+                .map(|((&i, &c), &n)| (i * c, n.min((i + 1) * c)))
+                .collect();
+            let selection: Vec<hdf5::SliceOrIndex> = bounds
+                .iter()
+                .map(|&(start, end)| (start..end).into())
+                .collect();
+            let block = read_selection::<T>(&dataset, selection)?;
+            // row-major multi-index of the element in the chunk
+            let mut local = vec![0; shape.len()];
+            for x in block {
+                let offset: usize = local
+                    .iter()
+                    .zip(&bounds)
+                    .zip(&strides)
+                    .map(|((&i, &(start, _)), &s)| (start + i) * s)
+                    .sum();
+                data[offset] = x;
+                for (i, &(start, end)) in local.iter_mut().zip(&bounds).rev() {
+                    *i += 1;
+                    if *i < end - start {
+                        break;
+                    }
+                    *i = 0;
+                }
+            }
+            // next chunk, the last dimension varying fastest
+            let mut done = true;
+            for (i, &n) in index.iter_mut().zip(&n_chunk).rev() {
+                *i += 1;
+                if *i < n {
+                    done = false;
+                    break;
+                }
+                *i = 0;
+            }
+            if done {
+                break;
+            }
+        }
+        Ok(MatVar {
+            name: dataset.name(),
+            shape,
+            data,
+        })
+    }
+    /// Read the real part of a Matlab complex array
+    ///
+    /// Only the `real` member of the HDF5 compound type is read,
+    /// the real part of a purely imaginary array stored without `real` member is zero
+    pub fn real_part<T: hdf5::H5Type + Default>(&self, name: &str) -> Result<MatVar<T>> {
+        let dataset = self.dataset(name)?;
+        let descriptor = dataset.dtype()?.to_descriptor()?;
+        if is_complex(&descriptor) && !has_member(&descriptor, "real") {
+            let shape = dataset.shape();
+            return Ok(MatVar {
+                name: dataset.name(),
+                data: (0..element_count(&shape)?).map(|_| T::default()).collect(),
+                shape,
+            });
+        }
+        self.complex_part::<RealPart<T>, T>(name, |x| x.0)
+    }
+    /// Read the imaginary part of a Matlab complex array
+    ///
+    /// Only the `imag` member of the HDF5 compound type is read
+    pub fn imag_part<T: hdf5::H5Type>(&self, name: &str) -> Result<MatVar<T>> {
+        self.complex_part::<ImagPart<T>, T>(name, |x| x.0)
+    }
+    /// Read a Matlab 1x1 complex array as its real and imaginary parts
+    pub fn complex_scalar(&self, name: &str) -> Result<(f64, f64)> {
+        let real = self.real_part::<f64>(name)?;
+        let imag = self.imag_part::<f64>(name)?;
+        match (real.data.as_slice(), imag.data.as_slice()) {
+            ([re], [im]) => Ok((*re, *im)),
+            _ => Err(Error::Shape {
+                expected: "a 1x1 array".to_string(),
+                found: real.matlab_shape(),
+            }),
+        }
+    }
+    fn complex_part<P: hdf5::H5Type, T>(&self, name: &str, part: fn(P) -> T) -> Result<MatVar<T>> {
+        let dataset = self.dataset(name)?;
+        let descriptor = dataset.dtype()?.to_descriptor()?;
+        if !is_complex(&descriptor) {
+            return Err(Error::Type {
+                name: name.to_string(),
+                expected: "a complex compound type".to_string(),
+                found: descriptor.to_string(),
+            });
+        }
+        Ok(MatVar {
+            name: dataset.name(),
+            shape: dataset.shape(),
+            data: dataset.read_raw::<P>()?.into_iter().map(part).collect(),
+        })
+    }
+    /// Read an array of HDF5 compound type records into `T`, a `#[derive(H5Type)]` struct
+    ///
+    /// The members of the records are matched by name with the fields of `T`
+    pub fn compound<T: hdf5::H5Type>(&self, name: &str) -> Result<MatVar<T>> {
+        let dataset = self.dataset(name)?;
+        let descriptor = dataset.dtype()?.to_descriptor()?;
+        if !matches!(descriptor, hdf5::types::TypeDescriptor::Compound(_)) {
+            return Err(Error::Type {
+                name: name.to_string(),
+                expected: "a compound type".to_string(),
+                found: descriptor.to_string(),
+            });
+        }
+        read_array(&dataset)
+    }
+    /// Read a Matlab sparse matrix
+    ///
+    /// The elements of a complex sparse matrix can be read into a compound type `T` with `real` and `imag` members,
+    /// or as `(real, imag)` pairs with [File::sparse_complex];
+    /// returns [Error::Sparse] if the column pointers and row indices do not index the stored elements
+    pub fn sparse<T: hdf5::H5Type>(&self, name: &str) -> Result<MatSparse<T>> {
+        self.read_sparse(name, |dataset| Ok(dataset.read_raw::<T>()?))
+    }
+    /// Read a Matlab complex sparse matrix, each element being a `(real, imag)` pair
+    ///
+    /// The real part of a purely imaginary matrix stored without `real` member is zero
+    pub fn sparse_complex<T: hdf5::H5Type + Default>(
+        &self,
+        name: &str,
+    ) -> Result<MatSparse<(T, T)>> {
+        self.read_sparse(name, |dataset| {
+            let descriptor = dataset.dtype()?.to_descriptor()?;
+            if !is_complex(&descriptor) {
+                return Err(Error::Type {
+                    name: name.to_string(),
+                    expected: "a complex compound type".to_string(),
+                    found: descriptor.to_string(),
+                });
+            }
+            let imag = dataset.read_raw::<ImagPart<T>>()?;
+            if !has_member(&descriptor, "real") {
+                return Ok(imag.into_iter().map(|im| (T::default(), im.0)).collect());
+            }
+            let real = dataset.read_raw::<RealPart<T>>()?;
+            Ok(real
+                .into_iter()
+                .zip(imag)
+                .map(|(re, im)| (re.0, im.0))
+                .collect())
+        })
+    }
+    /// Read a Matlab sparse logical matrix
+    pub fn sparse_logical(&self, name: &str) -> Result<MatSparse<bool>> {
+        let group = self.group(name)?;
+        check_class(&group, name, "logical")?;
+        self.read_sparse(name, |dataset| {
+            Ok(dataset
+                .read_raw::<u8>()?
+                .into_iter()
+                .map(|x| x != 0)
+                .collect())
+        })
+    }
+    fn read_sparse<T, F>(&self, name: &str, read_values: F) -> Result<MatSparse<T>>
+    where
+        F: Fn(&hdf5::Dataset) -> Result<Vec<T>>,
+    {
+        let group = self.group(name)?;
+        // the MATLAB_sparse attribute is the number of rows
+        let n_row = match group.attr("MATLAB_sparse") {
+            Ok(attr) => attr.read_scalar::<usize>()?,
+            _ => return Err(Error::Sparse(name.to_string())),
+        };
+        let column_pointers = match group.dataset("jc") {
+            Ok(dataset) => dataset.read_raw::<usize>()?,
+            _ => return Err(Error::Dataset(format!("{}/jc", name))),
+        };
+        // an all-zero sparse matrix has no data and row indices
+        let (row_indices, values) = match (group.dataset("ir"), group.dataset("data")) {
+            (Ok(ir), Ok(data)) => (ir.read_raw::<usize>()?, read_values(&data)?),
+            _ => (Vec::new(), Vec::new()),
+        };
+        // the column pointers must index the row indices and values from 0 to their common length
+        let nnz = values.len();
+        if column_pointers.first() != Some(&0)
+            || column_pointers.last() != Some(&nnz)
+            || column_pointers
+                .windows(2)
+                .any(|bounds| bounds[0] > bounds[1])
+            || row_indices.len() != nnz
+            || row_indices.iter().any(|&i| i >= n_row)
+        {
+            return Err(Error::Sparse(name.to_string()));
+        }
+        Ok(MatSparse {
+            name: group.name(),
+            n_row,
+            column_pointers,
+            row_indices,
+            values,
+        })
+    }
+    /// Read a Matlab logical scalar
+    pub fn flag(&self, name: &str) -> Result<bool> {
+        let dataset = self.dataset(name)?;
+        check_class(&dataset, name, "logical")?;
+        Ok(scalar_value::<u8>(&dataset)? != 0)
+    }
+    /// Read a Matlab logical array
+    ///
+    /// Matlab stores each element of a logical array as a `uint8` byte, 0 or 1.
+    /// Other writers may store a large logical array bit-packed, flagged with a `MATLAB_packed` attribute
+    /// holding the HDF5 shape of the unpacked array, each byte being unpacked into 8 booleans, most significant bit first
+    pub fn logical_array(&self, name: &str) -> Result<MatVar<bool>> {
+        let dataset = self.dataset(name)?;
+        check_class(&dataset, name, "logical")?;
+        let bytes = dataset.read_raw::<u8>()?;
+        match dataset.attr("MATLAB_packed") {
+            Ok(attr) => {
+                let shape = attr.read_raw::<usize>()?;
+                let n = element_count(&shape)?;
+                if bytes.len() != n.div_ceil(8) {
+                    return Err(Error::Shape {
+                        expected: format!("{} packed bytes", n.div_ceil(8)),
+                        found: matlab_shape(&dataset.shape()),
+                    });
+                }
+                Ok(MatVar {
+                    name: dataset.name(),
+                    shape,
+                    data: (0..n)
+                        .map(|i| bytes[i / 8] & (0x80 >> (i % 8)) != 0)
+                        .collect(),
+                })
+            }
+            Err(_) => Ok(MatVar {
+                name: dataset.name(),
+                shape: dataset.shape(),
+                data: bytes.into_iter().map(|x| x != 0).collect(),
+            }),
+        }
+    }
+    /// Returns the byte order a Matlab array is stored with
+    ///
+    /// Arrays are always read in the native byte order, HDF5 swapping the bytes if the stored byte order differs
+    pub fn byte_order(&self, name: &str) -> Result<hdf5::ByteOrder> {
+        Ok(self.dataset(name)?.dtype()?.byte_order())
+    }
+    /// Returns the number of bytes a Matlab array takes in memory, computed from its shape and type without reading it
+    ///
+    /// The element size of a complex array accounts for both the real and imaginary parts
+    pub fn size_bytes(&self, name: &str) -> Result<usize> {
+        let dataset = self.dataset(name)?;
+        let shape = dataset.shape();
+        element_count(&shape)?
+            .checked_mul(dataset.dtype()?.size())
+            .ok_or_else(|| Error::Shape {
+                expected: format!("at most {} bytes", usize::MAX),
+                found: matlab_shape(&shape),
+            })
+    }
+    /// Returns the ratio of the size of a Matlab array, see [File::size_bytes], to its HDF5 storage size
+    ///
+    /// Only the metadata are read; the ratio is 1 for an empty array
+    /// and infinite if the HDF5 storage of a non-empty array is not allocated
+    pub fn compression_ratio(&self, name: &str) -> Result<f64> {
+        let size = self.size_bytes(name)?;
+        let storage = self.dataset(name)?.storage_size();
+        Ok(match (size, storage) {
+            (0, _) => 1.,
+            (_, 0) => f64::INFINITY,
+            _ => size as f64 / storage as f64,
+        })
+    }
+    /// Read the bytes of a Matlab array as they are stored, without any type conversion
+    pub fn raw_bytes(&self, name: &str) -> Result<Vec<u8>> {
+        let mut buffer = vec![0u8; self.size_bytes(name)?];
+        let dataset = self.dataset(name)?;
+        let dtype = dataset.dtype()?;
+        // reading with the file datatype as memory datatype copies the stored bytes,
+        // holding the hdf5 crate lock as its safe API does
+        if hdf5::sync::sync(|| unsafe {
+            hdf5_sys::h5d::H5Dread(
+                dataset.id(),
+                dtype.id(),
+                hdf5_sys::h5s::H5S_ALL,
+                hdf5_sys::h5s::H5S_ALL,
+                hdf5_sys::h5p::H5P_DEFAULT,
+                buffer.as_mut_ptr() as *mut _,
+            )
+        }) < 0
+        {
+            return Err(hdf5::Error::from(format!("Reading the bytes of {} failed", name)).into());
+        }
+        Ok(buffer)
+    }
+    /// Read a Matlab array, checking that its Matlab shape is `expected`
+    pub fn array_shaped<T: hdf5::H5Type>(
+        &self,
+        name: &str,
+        expected: &[usize],
+    ) -> Result<MatVar<T>> {
+        let var = self.array(name)?;
+        let found = var.matlab_shape();
+        if found == expected {
+            Ok(var)
+        } else {
+            Err(Error::Shape {
+                expected: format!("Matlab shape {:?}", expected),
+                found,
+            })
+        }
+    }
+    /// Read a Matlab array if it exists
+    ///
+    /// Returns `Ok(None)` if there is no variable `name` and an error if the variable cannot be read
+    pub fn array_optional<T: hdf5::H5Type>(&self, name: &str) -> Result<Option<MatVar<T>>> {
+        if self.h5.link_exists(name) {
+            self.array(name).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+    /// Read a Matlab array of scaled values, returning the physical values `value * scale_factor + add_offset`
+    ///
+    /// The `scale_factor` and `add_offset` attributes follow the CF conventions
+    /// and default to 1 and 0 when missing
+    pub fn array_scaled(&self, name: &str) -> Result<MatVar<f64>> {
+        let dataset = self.dataset(name)?;
+        let attr = |attr: &str, default: f64| match dataset.attr(attr) {
+            Ok(attr) => attr.read_scalar::<f64>(),
+            _ => Ok(default),
+        };
+        let (scale, offset) = (attr("scale_factor", 1.)?, attr("add_offset", 0.)?);
+        let mut var = read_array::<f64>(&dataset)?;
+        var.data
+            .iter_mut()
+            .for_each(|value| *value = *value * scale + offset);
+        Ok(var)
+    }
+    /// Read the top-level Matlab arrays whose names match the glob `pattern`, sorted by name
+    ///
+    /// The pattern may contain `*` (any characters), `?` (one character) and `[...]` (one character of a set
+    /// like `[abc]` or `[0-9]`, `[!...]` for the complement)
+    pub fn array_matching<T: hdf5::H5Type>(&self, pattern: &str) -> Result<Vec<MatVar<T>>> {
+        let glob = Glob::new(pattern)?;
+        let mut names: Vec<String> = self
+            .variables()?
+            .into_iter()
+            .filter(|name| glob.matches(name))
+            .collect();
+        names.sort();
+        names.iter().map(|name| self.array(name)).collect()
+    }
+    /// Returns a reader of the bytes of a Matlab uint8 or int8 vector
+    ///
+    /// The vector is read block by block as the bytes are consumed, the int8 elements being reinterpreted as bytes
+    pub fn reader(&self, name: &str) -> Result<ByteReader> {
+        let dataset = self.dataset(name)?;
+        let class = matlab_class(&dataset)?;
+        if class != "uint8" && class != "int8" {
+            return Err(Error::Class {
+                name: name.to_string(),
+                expected: "uint8".to_string(),
+                found: class,
+            });
+        }
+        let shape = dataset.shape();
+        check_vector(&shape)?;
+        Ok(ByteReader {
+            axis: shape.iter().position(|&n| n > 1).unwrap_or(0),
+            signed: class == "int8",
+            position: 0,
+            block: std::io::Cursor::new(Vec::new()),
+            dataset,
+        })
+    }
+    /// Read each of the Matlab arrays `names` independently, returning the result of each read
+    ///
+    /// A variable that cannot be read does not prevent reading the others
+    pub fn try_read_many<T: hdf5::H5Type>(
+        &self,
+        names: &[&str],
+    ) -> Vec<(String, Result<MatVar<T>>)> {
+        names
+            .iter()
+            .map(|&name| (name.to_string(), self.array(name)))
+            .collect()
+    }
+    /// Read a Matlab array as `F` elements and converts them into `T`
+    ///
+    /// Returns [Error::Overflow] for the first element that does not fit in `T`
+    pub fn array_narrow<F, T>(&self, name: &str) -> Result<MatVar<T>>
+    where
+        F: hdf5::H5Type + Copy + std::fmt::Display,
+        T: std::convert::TryFrom<F>,
+    {
+        let var = self.array::<F>(name)?;
+        let data = var
+            .data
+            .iter()
+            .enumerate()
+            .map(|(index, &value)| {
+                T::try_from(value).map_err(|_| Error::Overflow {
+                    index,
+                    value: value.to_string(),
+                })
+            })
+            .collect::<Result<Vec<T>>>()?;
+        Ok(MatVar {
+            name: var.name,
+            shape: var.shape,
+            data,
+        })
+    }
+    /// Read a Matlab array into an ndarray view of the Matlab shape, the view element `[i, j, ...]`
+    /// being the Matlab element `A(i+1, j+1, ...)`
+    ///
+    /// The data are read straight into a view contiguous in column-major order,
+    /// any other view is filled from a copy of the data
+    ///
+    /// Returns [Error::Shape] if the view shape is not the Matlab shape
+    #[cfg(feature = "ndarray")]
+    pub fn read_into_ndarray<T, D>(
+        &self,
+        name: &str,
+        mut out: ndarray::ArrayViewMut<'_, T, D>,
+    ) -> Result<()>
+    where
+        T: hdf5::H5Type + Clone,
+        D: ndarray::Dimension,
+    {
+        let dataset = self.dataset(name)?;
+        let shape = matlab_shape(&dataset.shape());
+        if out.shape() != shape.as_slice() {
+            return Err(Error::Shape {
+                expected: format!("Matlab shape {:?}", out.shape()),
+                found: shape,
+            });
+        }
+        if out.t().is_standard_layout() {
+            if out.len() > 0 {
+                let mem_type = hdf5::Datatype::from_type::<T>()?;
+                if hdf5::sync::sync(|| unsafe {
+                    hdf5_sys::h5d::H5Dread(
+                        dataset.id(),
+                        mem_type.id(),
+                        hdf5_sys::h5s::H5S_ALL,
+                        hdf5_sys::h5s::H5S_ALL,
+                        hdf5_sys::h5p::H5P_DEFAULT,
+                        out.as_mut_ptr() as *mut _,
+                    )
+                }) < 0
+                {
+                    return Err(hdf5::Error::from(format!("Reading {} failed", name)).into());
+                }
+            }
+            return Ok(());
+        }
+        let strides = column_major_strides(&shape)?;
+        let data = dataset.read_raw::<T>()?;
+        // the view is iterated in row-major order and the data are column-major
+        let mut index = vec![0; shape.len()];
+        for x in out.iter_mut() {
+            let offset: usize = index.iter().zip(&strides).map(|(i, s)| i * s).sum();
+            *x = data[offset].clone();
+            for (i, &n) in index.iter_mut().zip(&shape).rev() {
+                *i += 1;
+                if *i < n {
+                    break;
+                }
+                *i = 0;
+            }
+        }
+        Ok(())
+    }
+    /// Write a 2D Matlab numeric array as comma separated values, one Matlab row per line
+    ///
+    /// The rows are read one at a time
+    pub fn export_csv<W: std::io::Write>(&self, name: &str, mut out: W) -> Result<()> {
+        let dataset = self.dataset(name)?;
+        let class = matlab_class(&dataset)?;
+        if !NUMERIC_CLASSES.contains(&class.as_str()) {
+            return Err(Error::Class {
+                name: name.to_string(),
+                expected: "a numeric class".to_string(),
+                found: class,
+            });
+        }
+        let descriptor = dataset.dtype()?.to_descriptor()?;
+        if is_complex(&descriptor) {
+            return Err(Error::Type {
+                name: name.to_string(),
+                expected: "a real numeric type".to_string(),
+                found: descriptor.to_string(),
+            });
+        }
+        let shape = dataset.shape();
+        if shape.len() != 2 {
+            return Err(Error::Shape {
+                expected: "a 2D array".to_string(),
+                found: matlab_shape(&shape),
+            });
+        }
+        // a Matlab row is an HDF5 column
+        for row in 0..shape[1] {
+            let selection: Vec<hdf5::SliceOrIndex> = vec![(..).into(), (row..row + 1).into()];
+            let values = read_selection::<f64>(&dataset, selection)?;
+            let line = values
+                .iter()
+                .map(|x| x.to_string())
+                .collect::<Vec<String>>()
+                .join(",");
+            writeln!(out, "{}", line)?;
+        }
+        Ok(())
+    }
+    /// Read every `stride`-th element of a Matlab vector, starting with the first one
+    ///
+    /// Only the selected elements are read; returns [Error::Stride] for a stride of 0
+    /// and [Error::Shape] if the array is not a vector
+    pub fn array_strided<T: hdf5::H5Type>(&self, name: &str, stride: usize) -> Result<MatVar<T>> {
+        if stride == 0 {
+            return Err(Error::Stride(name.to_string()));
+        }
+        let dataset = self.dataset(name)?;
+        let mut shape = dataset.shape();
+        check_vector(&shape)?;
+        let selection: Vec<hdf5::SliceOrIndex> = shape
+            .iter()
+            .map(|&n| {
+                if n > 1 {
+                    hdf5::SliceOrIndex::Slice {
+                        start: 0,
+                        step: stride,
+                        end: Some(n),
+                        block: false,
+                    }
+                } else {
+                    (..).into()
+                }
+            })
+            .collect();
+        let data = read_selection(&dataset, selection)?;
+        shape
+            .iter_mut()
+            .filter(|n| **n > 1)
+            .for_each(|n| *n = n.div_ceil(stride));
+        Ok(MatVar {
+            name: dataset.name(),
+            shape,
+            data,
+        })
+    }
+    /// Read a Matlab array, mapping the elements equal to `sentinel` to `None`
+    pub fn array_with_missing<T: hdf5::H5Type + PartialEq>(
+        &self,
+        name: &str,
+        sentinel: T,
+    ) -> Result<MatVar<Option<T>>> {
+        let var = self.array::<T>(name)?;
+        Ok(MatVar {
+            name: var.name,
+            shape: var.shape,
+            data: var
+                .data
+                .into_iter()
+                .map(|x| if x == sentinel { None } else { Some(x) })
+                .collect(),
+        })
+    }
+    /// Read a Matlab array in double precision, mapping NaN to `None`, see [MatVar::into_option_vec]
+    pub fn array_optional_f64(&self, name: &str) -> Result<Vec<Option<f64>>> {
+        Ok(self.array::<f64>(name)?.into_option_vec())
+    }
+    /// Checks if a Matlab array is stored with the exact HDF5 type of `T`
+    ///
+    /// When it is, [File::array] reads the data without any type conversion
+    pub fn is_exact_type<T: hdf5::H5Type>(&self, name: &str) -> Result<bool> {
+        Ok(self.dataset(name)?.dtype()?.to_descriptor()? == T::type_descriptor())
+    }
+    /// Read a Matlab array of half precision (2 bytes) floats
+    #[cfg(feature = "half")]
+    pub fn array_f16(&self, name: &str) -> Result<MatVar<half::f16>> {
+        let dataset = self.dataset(name)?;
+        let dtype = dataset.dtype()?;
+        match dtype.to_descriptor()? {
+            hdf5::types::TypeDescriptor::Float(_) if dtype.size() == 2 => read_array(&dataset),
+            descriptor => Err(Error::Type {
+                name: name.to_string(),
+                expected: "a 2 bytes float".to_string(),
+                found: descriptor.to_string(),
+            }),
+        }
+    }
+    pub fn structure(&self, name: &str) -> Result<MatStruct> {
+        open_struct(&self.h5, name)
+    }
+    /// Checks the Matlab class and shape of the variables in `schema`, returning all the mismatches
+    ///
+    /// The shape of a variable stored as a HDF5 group (e.g. a struct) is not checked,
+    /// a variable whose class or shape cannot be read is reported as [SchemaError::Unreadable]
+    pub fn validate(&self, schema: &[VarSpec]) -> Result<Vec<SchemaError>> {
+        let mut errors = Vec::new();
+        for spec in schema {
+            if !self.h5.link_exists(&spec.name) {
+                errors.push(SchemaError::Missing(spec.name.clone()));
+                continue;
+            }
+            let class_and_shape = match self.h5.group(&spec.name) {
+                Ok(group) => matlab_class(&group).map(|class| (class, None)),
+                _ => self.dataset(&spec.name).and_then(|dataset| {
+                    Ok((
+                        matlab_class(&dataset)?,
+                        Some(matlab_shape(&dataset.shape())),
+                    ))
+                }),
+            };
+            let (class, shape) = match class_and_shape {
+                Ok(it) => it,
+                Err(e) => {
+                    errors.push(SchemaError::Unreadable {
+                        name: spec.name.clone(),
+                        error: e.to_string(),
+                    });
+                    continue;
+                }
+            };
+            if class != spec.class {
+                errors.push(SchemaError::Class {
+                    name: spec.name.clone(),
+                    expected: spec.class.clone(),
+                    found: class,
+                });
+            }
+            if let (Some(expected), Some(found)) = (&spec.shape, shape) {
+                if *expected != found {
+                    errors.push(SchemaError::Shape {
+                        name: spec.name.clone(),
+                        expected: expected.clone(),
+                        found,
+                    });
+                }
+            }
+        }
+        Ok(errors)
+    }
+    /// Returns the content of the `#subsystem#` group, `None` if the file has no such group
+    ///
+    /// The object variables referencing the subsystem are recognized by their `MATLAB_object_decode` attribute
+    pub fn subsystem_info(&self) -> Result<Option<SubsystemInfo>> {
+        if !self.h5.link_exists("#subsystem#") {
+            return Ok(None);
+        }
+        let members = self.group("#subsystem#")?.member_names()?;
+        let mut classes = Vec::new();
+        for name in self.variables()? {
+            let class = match self.h5.group(&name) {
+                Ok(group) => object_class(&group),
+                _ => {
+                    let dataset = self.dataset(&name)?;
+                    object_class(&dataset)
+                }
+            };
+            if let Some(class) = class {
+                if !classes.contains(&class) {
+                    classes.push(class);
+                }
+            }
+        }
+        Ok(Some(SubsystemInfo { members, classes }))
+    }
+    /// Returns the target path of the variable `name` if it is an HDF5 soft link, `None` otherwise
+    ///
+    /// Soft links are followed when reading, an alias reads the same as its target
+    pub fn link_target(&self, name: &str) -> Result<Option<String>> {
+        if !self.h5.link_exists(name) {
+            return Err(Error::Dataset(name.to_string()));
+        }
+        Ok(soft_link(&self.h5, name))
+    }
+    /// Returns the names and kinds of the members of the HDF5 group `path`, `/` being the file root
+    pub fn children(&self, path: &str) -> Result<Vec<(String, NodeKind)>> {
+        let group = self.group(path)?;
+        group
+            .member_names()?
+            .into_iter()
+            .map(|name| {
+                let kind = match group.group(&name) {
+                    Ok(child) => NodeKind::Group(matlab_class(&child).ok()),
+                    _ => match group.dataset(&name) {
+                        Ok(child) => NodeKind::Dataset(matlab_class(&child).ok()),
+                        _ => return Err(Error::Dataset(format!("{}/{}", group.name(), name))),
+                    },
+                };
+                Ok((name, kind))
+            })
+            .collect()
+    }
+    /// Returns the metadata of the top-level Matlab variables, reading only the attributes and dataspaces
+    pub fn manifest(&self) -> Result<Vec<VarInfo>> {
+        self.variables()?
+            .into_iter()
+            .map(|name| {
+                if let Ok(group) = self.h5.group(&name) {
+                    Ok(VarInfo {
+                        path: group.name(),
+                        class: matlab_class(&group)?,
+                        shape: Vec::new(),
+                        dtype: None,
+                        size_bytes: 0,
+                        name,
+                    })
+                } else {
+                    let dataset = self.dataset(&name)?;
+                    Ok(VarInfo {
+                        path: dataset.name(),
+                        class: matlab_class(&dataset)?,
+                        shape: matlab_shape(&dataset.shape()),
+                        dtype: Some(dataset.dtype()?.to_descriptor()?),
+                        size_bytes: self.size_bytes(&name)?,
+                        name,
+                    })
+                }
+            })
+            .collect()
+    }
+    /// Checks that all the datasets of the file can be read, returning [Error::Verify] for the first one that cannot
+    ///
+    /// The first element of each numeric or complex dataset is read, decompressing its first chunk
+    pub fn verify(&self) -> Result<()> {
+        verify_group(&self.h5)
+    }
+    /// Returns the names of the top-level Matlab variables
+    pub fn variables(&self) -> Result<Vec<String>> {
+        Ok(self
+            .h5
+            .member_names()?
+            .into_iter()
+            .filter(|name| !name.starts_with('#'))
+            .collect())
+    }
+    /// Read a Matlab variable according to its Matlab class
+    ///
+    /// The rows of a char matrix are joined by newlines, see [File::text].
+    /// A variable of any other class than numeric, logical, char and struct is returned as [MatValue::Unsupported]
+    /// with the shape of its HDF5 dataset (empty for a HDF5 group)
+    pub fn read(&self, name: &str) -> Result<MatValue> {
+        let (class, shape) = if let Ok(group) = self.h5.group(name) {
+            let class = matlab_class(&group)?;
+            if let Some(handler) = self.handlers.get(&class) {
+                return handler(&group);
+            }
+            (class, Vec::new())
+        } else {
+            let dataset = self.dataset(name)?;
+            let class = matlab_class(&dataset)?;
+            if let Some(value) = empty_value(&dataset, &class)? {
+                return Ok(value);
+            }
+            (class, dataset.shape())
+        };
+        match class.as_str() {
+            class if NUMERIC_CLASSES.contains(&class) => Ok(MatValue::Numeric(self.array(name)?)),
+            "logical" => Ok(MatValue::Logical(self.logical_array(name)?)),
+            "char" => Ok(MatValue::Char(char_string(&self.dataset(name)?)?)),
+            "struct" => Ok(MatValue::Struct(self.structure(name)?)),
+            _ => Ok(MatValue::Unsupported { class, shape }),
+        }
+    }
+    /// Returns a one-line summary of a Matlab variable: class, Matlab shape and HDF5 type
+    ///
+    /// The summary of a small real numeric array adds the minimum, maximum and mean of the elements
+    pub fn summarize(&self, name: &str) -> Result<String> {
+        if let Ok(group) = self.h5.group(name) {
+            return Ok(format!("{}: {} group", name, matlab_class(&group)?));
+        }
+        let dataset = self.dataset(name)?;
+        let class = matlab_class(&dataset)?;
+        let shape = matlab_shape(&dataset.shape());
+        let descriptor = dataset.dtype()?.to_descriptor()?;
+        let mut summary = format!(
+            "{}: {} {} ({})",
+            name,
+            class,
+            shape
+                .iter()
+                .map(|n| n.to_string())
+                .collect::<Vec<String>>()
+                .join("x"),
+            descriptor
+        );
+        let n = element_count(&shape)?;
+        if NUMERIC_CLASSES.contains(&class.as_str())
+            && !is_complex(&descriptor)
+            && n > 0
+            && n <= SUMMARY_ELEMENTS
+        {
+            let data = dataset.read_raw::<f64>()?;
+            let min = data.iter().copied().fold(f64::INFINITY, f64::min);
+            let max = data.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+            let mean = data.iter().sum::<f64>() / n as f64;
+            summary.push_str(&format!(", min {} max {} mean {}", min, max, mean));
+        }
+        Ok(summary)
+    }
+    /// Iterates over the top-level Matlab variables, reading each one as it is yielded
+    ///
+    /// A variable that cannot be read is yielded as an error and the iteration goes on
+    pub fn iter(&self) -> impl Iterator<Item = Result<(String, MatValue)>> + '_ {
+        let (names, error) = match self.variables() {
+            Ok(names) => (names, None),
+            Err(e) => (Vec::new(), Some(e)),
+        };
+        error.into_iter().map(Err).chain(
+            names
+                .into_iter()
+                .map(move |name| self.read(&name).map(|value| (name, value))),
+        )
+    }
+    /// Read a Matlab char matrix as a multi-line text
+    ///
+    /// Each row of the matrix is a line with the trailing padding spaces removed
+    pub fn text(&self, name: &str) -> Result<String> {
+        let dataset = self.dataset(name)?;
+        check_class(&dataset, name, "char")?;
+        Ok(char_rows(&dataset.read_raw::<u16>()?, &dataset.shape()).join("\n"))
+    }
+    /// Read a Matlab 3D char array as pages of lines, the page `k` being the char matrix `A(:, :, k+1)`
+    ///
+    /// A char matrix is a single page and each line has the trailing padding spaces removed
+    pub fn string_pages(&self, name: &str) -> Result<Vec<Vec<String>>> {
+        let dataset = self.dataset(name)?;
+        check_class(&dataset, name, "char")?;
+        // empty arrays are stored as a dataset of their dimensions
+        if is_matlab_empty(&dataset) {
+            return Ok(Vec::new());
+        }
+        let shape = dataset.shape();
+        if shape.len() > 3 {
+            return Err(Error::Shape {
+                expected: "a 3D char array".to_string(),
+                found: matlab_shape(&shape),
+            });
+        }
+        let page_shape = &shape[shape.len().saturating_sub(2)..];
+        let page_len = element_count(page_shape)?;
+        let data = dataset.read_raw::<u16>()?;
+        // the pages are contiguous in Matlab column-major order
+        Ok((0..data.len() / page_len.max(1))
+            .map(|k| char_rows(&data[k * page_len..(k + 1) * page_len], page_shape))
+            .collect())
+    }
+    /// Read a Matlab cell array of char arrays as strings, in Matlab linear index order
+    ///
+    /// An empty cell is an empty list and an empty char element is an empty string
+    pub fn string_cell(&self, name: &str) -> Result<Vec<String>> {
+        let dataset = self.dataset(name)?;
+        check_class(&dataset, name, "cell")?;
+        if is_matlab_empty(&dataset) {
+            return Ok(Vec::new());
+        }
+        self.cell(&self.h5, name)?
+            .iter()
+            .map(|element| {
+                check_class(element, &element.name(), "char")?;
+                // empty arrays are stored as a dataset of their dimensions
+                if is_matlab_empty(element) {
+                    Ok(String::new())
+                } else {
+                    char_string(element)
+                }
+            })
+            .collect()
+    }
+    /// Read the function name of a Matlab function handle, the source text of an anonymous function
+    ///
+    /// The name is the char member `function` of the `function_handle` group or of its nested `function_handle` struct
+    pub fn function_handle(&self, name: &str) -> Result<String> {
+        let group = self.group(name)?;
+        check_class(&group, name, "function_handle")?;
+        let dataset = group
+            .dataset("function")
+            .or_else(|_| group.dataset("function_handle/function"));
+        match dataset {
+            Ok(dataset) => {
+                check_class(&dataset, &dataset.name(), "char")?;
+                char_string(&dataset)
+            }
+            _ => Err(Error::Dataset(format!("{}/function", name))),
+        }
+    }
+    /// Dereferences the elements of a Matlab cell stored in `group`
+    fn cell(&self, group: &hdf5::Group, name: &str) -> Result<Vec<hdf5::Dataset>> {
+        let dataset = match group.dataset(name) {
+            Ok(it) => it,
+            _ => return Err(Error::Dataset(format!("{}/{}", group.name(), name))),
+        };
+        dataset
+            .read_raw::<hdf5::ObjectReference1>()?
+            .iter()
+            .map(|reference| match self.h5.dereference(reference)? {
+                hdf5::ReferencedObject::Dataset(dataset) => Ok(dataset),
+                _ => Err(Error::Dataset(format!("{}/{}", group.name(), name))),
+            })
+            .collect()
+    }
+}
+
+/// Matlab 7.3 mat file that can be shared between threads
+///
+/// The HDF5 library is not reentrant: the hdf5 crate serializes all the HDF5 calls behind a global lock.
+/// Concurrent reads are therefore not faster than sequential ones and `SharedFile` simply guards the
+/// [File] with a mutex, each clone referring to the same opened file.
+#[derive(Clone)]
+pub struct SharedFile(std::sync::Arc<std::sync::Mutex<File>>);
+impl SharedFile {
+    pub fn new<P: AsRef<std::path::Path>>(file: P) -> Result<Self> {
+        Ok(File::new(file)?.into())
+    }
+    /// Calls `f` with the [File], locking it for the duration of the call
+    pub fn with<R>(&self, f: impl FnOnce(&File) -> R) -> R {
+        // a panic while reading leaves the file usable
+        f(&self.0.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+    /// Read a Matlab array, see [File::array]
+    pub fn array<T: hdf5::H5Type>(&self, name: &str) -> Result<MatVar<T>> {
+        self.with(|file| file.array(name))
+    }
+    /// Read a Matlab variable according to its Matlab class, see [File::read]
+    pub fn read(&self, name: &str) -> Result<MatValue> {
+        self.with(|file| file.read(name))
+    }
+    /// Returns the names of the top-level Matlab variables
+    pub fn variables(&self) -> Result<Vec<String>> {
+        self.with(|file| file.variables())
+    }
+}
+impl From<File> for SharedFile {
+    fn from(file: File) -> Self {
+        Self(std::sync::Arc::new(std::sync::Mutex::new(file)))
+    }
+}
+
+/// Memory order of the elements read by a [ReadBuilder]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Orientation {
+    /// Matlab order, see [File::array]
+    #[default]
+    ColumnMajor,
+    /// Transposed order, see [File::array_row_major]
+    RowMajor,
+}
+/// Handling of the NaN and infinite elements of a Matlab array, see [MatVar::into_matrix_checked]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum NanPolicy<T> {
+    /// The elements are kept
+    #[default]
+    Allow,
+    /// [Error::NonFinite] is returned with the Matlab linear indices of the elements
+    Error,
+    /// The elements are replaced by the value
+    Replace(T),
+}
+/// Matlab array reader combining the read options, see [File::read_builder]
+pub struct ReadBuilder<'a, T> {
+    file: &'a File,
+    name: String,
+    orientation: Orientation,
+    expected: Option<Vec<usize>>,
+    squeeze: bool,
+    element: std::marker::PhantomData<T>,
+}
+impl<'a, T> ReadBuilder<'a, T> {
+    /// Reads the elements as `U`
+    pub fn as_type<U>(self) -> ReadBuilder<'a, U> {
+        ReadBuilder {
+            file: self.file,
+            name: self.name,
+            orientation: self.orientation,
+            expected: self.expected,
+            squeeze: self.squeeze,
+            element: std::marker::PhantomData,
+        }
+    }
+    pub fn orientation(self, orientation: Orientation) -> Self {
+        Self {
+            orientation,
+            ..self
+        }
+    }
+    /// Checks that the array read has the Matlab shape `expected`
+    pub fn expect_shape(self, expected: &[usize]) -> Self {
+        Self {
+            expected: Some(expected.to_vec()),
+            ..self
+        }
+    }
+    /// Removes the singleton dimensions of the array, see [MatVar::squeeze]
+    pub fn squeeze(self) -> Self {
+        Self {
+            squeeze: true,
+            ..self
+        }
+    }
+}
+impl<'a, T: hdf5::H5Type + Clone + Default> ReadBuilder<'a, T> {
+    pub fn finish(self) -> Result<MatVar<T>> {
+        // the shape of a row-major array is already in Matlab order
+        let (var, found) = match self.orientation {
+            Orientation::ColumnMajor => {
+                let var = self.file.array(&self.name)?;
+                let found = var.matlab_shape();
+                (var, found)
+            }
+            Orientation::RowMajor => {
+                let var = self.file.array_row_major(&self.name)?;
+                let found = var.shape.clone();
+                (var, found)
+            }
+        };
+        if let Some(expected) = self.expected {
+            if found != expected {
+                return Err(Error::Shape {
+                    expected: format!("Matlab shape {:?}", expected),
+                    found,
+                });
+            }
+        }
+        Ok(if self.squeeze { var.squeeze() } else { var })
+    }
+}
+
+/// Owning iterator over the top-level Matlab variables of a [File]
+///
+/// The file is closed when the iterator is dropped, see [File::iter] for the borrowing iterator
+pub struct IntoIter {
+    file: File,
+    names: std::vec::IntoIter<String>,
+    error: Option<Error>,
+}
+impl Iterator for IntoIter {
+    type Item = Result<(String, MatValue)>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(e) = self.error.take() {
+            return Some(Err(e));
+        }
+        let name = self.names.next()?;
+        Some(self.file.read(&name).map(|value| (name, value)))
+    }
+}
+impl IntoIterator for File {
+    type Item = Result<(String, MatValue)>;
+    type IntoIter = IntoIter;
+    /// Consumes the file, reading each top-level variable as it is yielded
+    fn into_iter(self) -> IntoIter {
+        let (names, error) = match self.variables() {
+            Ok(names) => (names, None),
+            Err(e) => (Vec::new(), Some(e)),
+        };
+        IntoIter {
+            file: self,
+            names: names.into_iter(),
+            error,
+        }
+    }
+}
+
+/// Reader of the bytes of a Matlab uint8 or int8 vector, see [File::reader]
+pub struct ByteReader {
+    dataset: hdf5::Dataset,
+    axis: usize,
+    signed: bool,
+    position: usize,
+    block: std::io::Cursor<Vec<u8>>,
+}
+impl ByteReader {
+    /// Reads the next block of at most [BLOCK_BYTES] bytes
+    fn read_block(&mut self) -> Result<Vec<u8>> {
+        let shape = self.dataset.shape();
+        let n = element_count(&shape)?;
+        if self.position >= n {
+            return Ok(Vec::new());
+        }
+        let end = n.min(self.position + BLOCK_BYTES);
+        let selection: Vec<hdf5::SliceOrIndex> = (0..shape.len())
+            .map(|k| {
+                if k == self.axis {
+                    (self.position..end).into()
+                } else {
+                    (..).into()
+                }
+            })
+            .collect();
+        let block = if self.signed {
+            read_selection::<i8>(&self.dataset, selection)?
+                .into_iter()
+                .map(|x| x as u8)
+                .collect()
+        } else {
+            read_selection::<u8>(&self.dataset, selection)?
+        };
+        self.position = end;
+        Ok(block)
+    }
+}
+impl std::io::Read for ByteReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.block.position() as usize == self.block.get_ref().len() {
+            let block = self.read_block().map_err(std::io::Error::other)?;
+            self.block = std::io::Cursor::new(block);
+        }
+        self.block.read(buf)
+    }
+}
+
+/// Element of a glob pattern
+#[derive(Debug, PartialEq)]
+enum GlobToken {
+    Any,
+    One,
+    Char(char),
+    Set {
+        ranges: Vec<(char, char)>,
+        negated: bool,
+    },
+}
+/// Glob pattern, see [File::array_matching]
+struct Glob(Vec<GlobToken>);
+impl Glob {
+    fn new(pattern: &str) -> Result<Self> {
+        let mut chars = pattern.chars();
+        let mut tokens = Vec::new();
+        while let Some(c) = chars.next() {
+            tokens.push(match c {
+                '*' => GlobToken::Any,
+                '?' => GlobToken::One,
+                '[' => {
+                    let mut set: Vec<char> = Vec::new();
+                    loop {
+                        match chars.next() {
+                            Some(']') if !set.is_empty() && set != ['!'] => break,
+                            Some(c) => set.push(c),
+                            None => return Err(Error::Pattern(pattern.to_string())),
+                        }
+                    }
+                    let negated = set[0] == '!';
+                    let set = &set[negated as usize..];
+                    let mut ranges = Vec::new();
+                    let mut k = 0;
+                    while k < set.len() {
+                        if k + 2 < set.len() && set[k + 1] == '-' {
+                            ranges.push((set[k], set[k + 2]));
+                            k += 3;
+                        } else {
+                            ranges.push((set[k], set[k]));
+                            k += 1;
+                        }
+                    }
+                    GlobToken::Set { ranges, negated }
+                }
+                c => GlobToken::Char(c),
+            });
+        }
+        Ok(Self(tokens))
+    }
+    fn matches(&self, name: &str) -> bool {
+        let name: Vec<char> = name.chars().collect();
+        Self::match_tokens(&self.0, &name)
+    }
+    fn match_tokens(tokens: &[GlobToken], name: &[char]) -> bool {
+        match tokens.split_first() {
+            None => name.is_empty(),
+            Some((GlobToken::Any, rest)) => {
+                (0..=name.len()).any(|k| Self::match_tokens(rest, &name[k..]))
+            }
+            Some((token, rest)) => match name.split_first() {
+                Some((&c, name)) => {
+                    let matched = match token {
+                        GlobToken::Char(t) => *t == c,
+                        GlobToken::Set { ranges, negated } => {
+                            ranges.iter().any(|&(a, b)| a <= c && c <= b) != *negated
+                        }
+                        _ => true,
+                    };
+                    matched && Self::match_tokens(rest, name)
+                }
+                None => false,
+            },
+        }
+    }
+}
+
+/// Opens the Matlab struct `name` within `parent`
+fn open_struct(parent: &hdf5::Group, name: &str) -> Result<MatStruct> {
+    if let Ok(dataset) = parent.dataset(name) {
+        // empty structs and cells are stored as a dataset of their dimensions
+        return match matlab_class(&dataset)?.as_str() {
+            "struct" | "cell" if is_matlab_empty(&dataset) => Ok(MatStruct {
+                field_names: Vec::new(),
+                len: 0,
+                group: None,
+            }),
+            _ => Err(Error::Struct),
+        };
+    }
+    let group = match parent.group(name) {
+        Ok(it) => it,
+        _ => return Err(Error::Group(name.to_string())),
+    };
+    if group.attr("MATLAB_class").is_err() {
+        return Err(Error::NotMatlab(group.name()));
+    }
+    if matlab_class(&group)? != "struct" {
+        return Err(Error::Struct);
+    }
+    if !group
+        .attr_names()?
+        .iter()
+        .any(|attr| attr == "MATLAB_fields")
+    {
+        return Ok(MatStruct {
+            field_names: Vec::new(),
+            len: 1,
+            group: Some(group),
+        });
+    }
+    let field_names = field_names(&group.attr("MATLAB_fields")?)?;
+    // the fields of a struct array are datasets of references without a Matlab class
+    let len = field_names
+        .first()
+        .and_then(|field| group.dataset(field).ok())
+        .filter(|dataset| dataset.attr("MATLAB_class").is_err())
+        .map_or(1, |dataset| dataset.size());
+    Ok(MatStruct {
+        field_names,
+        len,
+        group: Some(group),
+    })
+}
+/// Checks if a HDF5 type is the compound type of Matlab complex numbers
+///
+/// A purely imaginary array may be stored with the `imag` member only
+fn is_complex(descriptor: &hdf5::types::TypeDescriptor) -> bool {
+    match descriptor {
+        hdf5::types::TypeDescriptor::Compound(compound) => {
+            has_member(descriptor, "imag")
+                && compound
+                    .fields
+                    .iter()
+                    .all(|field| field.name == "real" || field.name == "imag")
+        }
+        _ => false,
+    }
+}
+/// Checks if a HDF5 compound type has the member `name`
+fn has_member(descriptor: &hdf5::types::TypeDescriptor, name: &str) -> bool {
+    match descriptor {
+        hdf5::types::TypeDescriptor::Compound(compound) => {
+            compound.fields.iter().any(|field| field.name == name)
+        }
+        _ => false,
+    }
+}
+/// Defines a HDF5 compound type with the single member `$member` of type `T`,
+/// used to read only that member of a larger compound type
+macro_rules! compound_member {
+    ($name:ident, $member:expr) => {
+        #[repr(transparent)]
+        struct $name<T>(T);
+        unsafe impl<T: hdf5::H5Type> hdf5::H5Type for $name<T> {
+            fn type_descriptor() -> hdf5::types::TypeDescriptor {
+                hdf5::types::TypeDescriptor::Compound(hdf5::types::CompoundType {
+                    fields: vec![hdf5::types::CompoundField {
+                        name: $member.to_string(),
+                        ty: T::type_descriptor(),
+                        offset: 0,
+                        index: 0,
+                    }],
+                    size: std::mem::size_of::<T>(),
+                })
+            }
+        }
+    };
+}
+compound_member!(RealPart, "real");
+compound_member!(ImagPart, "imag");
+/// Reads a dataset into a Matlab array
+fn read_array<T: hdf5::H5Type>(dataset: &hdf5::Dataset) -> Result<MatVar<T>> {
+    Ok(MatVar {
+        name: dataset.name(),
+        shape: dataset.shape(),
+        data: dataset.read_raw::<T>()?,
+    })
+}
+/// Reads the elements of a dataset hyperslab in HDF5 row-major order
+fn read_selection<T: hdf5::H5Type>(
+    dataset: &hdf5::Dataset,
+    selection: Vec<hdf5::SliceOrIndex>,
+) -> Result<Vec<T>> {
+    let array: ndarray::ArrayD<T> = dataset.read_slice(hdf5::Hyperslab::from(selection))?;
+    Ok(array.into_raw_vec())
+}
+/// Returns the empty numeric, logical or char array of a dataset with the `MATLAB_empty` attribute,
+/// `None` for the other datasets
+///
+/// An empty array is stored as its Matlab dimensions
+fn empty_value(dataset: &hdf5::Dataset, class: &str) -> Result<Option<MatValue>> {
+    use std::convert::TryFrom;
+    if !is_matlab_empty(dataset) {
+        return Ok(None);
+    }
+    let mut shape = dataset
+        .read_raw::<u64>()?
+        .into_iter()
+        .enumerate()
+        .map(|(index, n)| {
+            usize::try_from(n).map_err(|_| Error::Overflow {
+                index,
+                value: n.to_string(),
+            })
+        })
+        .collect::<Result<Vec<usize>>>()?;
+    shape.reverse();
+    let name = dataset.name();
+    Ok(match class {
+        class if NUMERIC_CLASSES.contains(&class) => Some(MatValue::Numeric(MatVar {
+            name,
+            shape,
+            data: Vec::new(),
+        })),
+        "logical" => Some(MatValue::Logical(MatVar {
+            name,
+            shape,
+            data: Vec::new(),
+        })),
+        "char" => Some(MatValue::Char(String::new())),
+        _ => None,
+    })
+}
+/// Size in bytes of the blocks read by [read_blocks] for non-chunked datasets
+const BLOCK_BYTES: usize = 1 << 20;
+/// Reads a dataset block by block along the first HDF5 dimension, passing the data of each block to `f`
+///
+/// The blocks match the HDF5 chunks of chunked datasets and are about [BLOCK_BYTES] long otherwise
+fn read_blocks<T, F>(dataset: &hdf5::Dataset, mut f: F) -> Result<()>
+where
+    T: hdf5::H5Type,
+    F: FnMut(Vec<T>) -> Result<()>,
+{
+    let shape = dataset.shape();
+    let n = match shape.first() {
+        Some(&n) => n,
+        None => return f(dataset.read_raw::<T>()?),
+    };
+    let row_bytes =
+        checked_product(&[element_count(&shape[1..])?.max(1), dataset.dtype()?.size()])?;
+    let rows = dataset
+        .chunk()
+        .and_then(|chunk| chunk.first().copied())
+        .unwrap_or(BLOCK_BYTES / row_bytes)
+        .max(1);
+    for start in (0..n).step_by(rows) {
+        let selection: Vec<hdf5::SliceOrIndex> =
+            std::iter::once((start..n.min(start + rows)).into())
+                .chain(shape[1..].iter().map(|_| (..).into()))
+                .collect();
+        f(read_selection(dataset, selection)?)?;
+    }
+    Ok(())
+}
+/// Reads the `MATLAB_class` attribute
+fn matlab_class(location: &hdf5::Location) -> Result<String> {
+    Ok(location
+        .attr("MATLAB_class")?
+        .read_scalar::<FixedAscii<256>>()?
+        .as_str()
+        .to_string())
+}
+/// Checks the datasets of `group` and of its subgroups, see [File::verify]
+fn verify_group(group: &hdf5::Group) -> Result<()> {
+    for name in group.member_names()? {
+        if let Ok(subgroup) = group.group(&name) {
+            verify_group(&subgroup)?;
+            continue;
+        }
+        let path = format!("{}/{}", group.name().trim_end_matches('/'), name);
+        let dataset = match group.dataset(&name) {
+            Ok(it) => it,
+            _ => {
+                return Err(Error::Verify {
+                    name: path.clone(),
+                    error: Box::new(Error::Dataset(path)),
+                })
+            }
+        };
+        probe(&dataset).map_err(|error| Error::Verify {
+            name: path,
+            error: Box::new(error),
+        })?;
+    }
+    Ok(())
+}
+/// Reads the first element of a numeric or complex dataset
+fn probe(dataset: &hdf5::Dataset) -> Result<()> {
+    use hdf5::types::TypeDescriptor;
+    let shape = dataset.shape();
+    if element_count(&shape)? == 0 {
+        return Ok(());
+    }
+    let selection: Vec<hdf5::SliceOrIndex> = shape.iter().map(|_| (0..1).into()).collect();
+    match dataset.dtype()?.to_descriptor()? {
+        TypeDescriptor::Float(_) | TypeDescriptor::Integer(_) | TypeDescriptor::Unsigned(_) => {
+            read_selection::<f64>(dataset, selection)?;
+        }
+        descriptor if is_complex(&descriptor) => {
+            read_selection::<ImagPart<f64>>(dataset, selection)?;
+        }
+        _ => (),
+    }
+    Ok(())
+}
+/// Returns the Matlab class of an object stored in the `#subsystem#` group
+fn object_class(location: &hdf5::Location) -> Option<String> {
+    location
+        .attr("MATLAB_object_decode")
+        .ok()
+        .and_then(|_| matlab_class(location).ok())
+}
+/// Checks the `MATLAB_empty` attribute of empty Matlab containers
+fn is_matlab_empty(location: &hdf5::Location) -> bool {
+    location
+        .attr("MATLAB_empty")
+        .and_then(|attr| attr.read_scalar::<u8>())
+        .is_ok_and(|empty| empty != 0)
+}
+/// Checks that the `MATLAB_class` attribute is `expected`
+fn check_class(location: &hdf5::Location, name: &str, expected: &str) -> Result<()> {
+    let found = matlab_class(location)?;
+    if found == expected {
+        Ok(())
+    } else {
+        Err(Error::Class {
+            name: name.to_string(),
+            expected: expected.to_string(),
+            found,
+        })
+    }
+}
+/// Latest HDF5 superblock version the linked HDF5 library reads: 2 for HDF5 1.8, 3 from HDF5 1.10
+fn max_superblock_version() -> u8 {
+    match hdf5::library_version() {
+        (1, minor, _) if minor < 10 => 2,
+        _ => 3,
+    }
+}
+/// Reads the superblock version of a HDF5 file
+///
+/// The HDF5 signature is searched at offset 0 and at the powers of 2 from 512,
+/// mat files starting with a 512 bytes user block
+fn superblock_version(file: &std::path::Path) -> Option<u8> {
+    use std::io::{Read, Seek, SeekFrom};
+    const SIGNATURE: [u8; 8] = [0x89, b'H', b'D', b'F', b'\r', b'\n', 0x1a, b'\n'];
+    let mut file = std::fs::File::open(file).ok()?;
+    let len = file.metadata().ok()?.len();
+    let mut bytes = [0u8; 9];
+    std::iter::once(0)
+        .chain(std::iter::successors(Some(512u64), |&offset| {
+            offset.checked_mul(2)
+        }))
+        .take_while(|&offset| offset + 9 <= len)
+        .find_map(|offset| {
+            file.seek(SeekFrom::Start(offset)).ok()?;
+            file.read_exact(&mut bytes).ok()?;
+            (bytes[..8] == SIGNATURE).then_some(bytes[8])
+        })
+}
+/// Returns the value of the HDF5 soft or external link `name`, `None` for a hard link
+fn link_value(location: &hdf5::Location, name: &str) -> Option<Vec<u8>> {
+    let c_name = std::ffi::CString::new(name).ok()?;
+    let mut info = std::mem::MaybeUninit::<hdf5_sys::h5l::H5L_info_t>::uninit();
+    if hdf5::sync::sync(|| unsafe {
+        hdf5_sys::h5l::H5Lget_info(
+            location.id(),
+            c_name.as_ptr(),
+            info.as_mut_ptr(),
+            hdf5_sys::h5p::H5P_DEFAULT,
+        )
+    }) < 0
+    {
+        return None;
+    }
+    let info = unsafe { info.assume_init() };
+    // the union holds the object address of a hard link
+    if info.type_ == hdf5_sys::h5l::H5L_type_t::H5L_TYPE_HARD {
+        return None;
+    }
+    let mut value = vec![0u8; unsafe { info.u.val_size }];
+    if hdf5::sync::sync(|| unsafe {
+        hdf5_sys::h5l::H5Lget_val(
+            location.id(),
+            c_name.as_ptr(),
+            value.as_mut_ptr() as *mut _,
+            value.len(),
+            hdf5_sys::h5p::H5P_DEFAULT,
+        )
+    }) < 0
+    {
+        return None;
+    }
+    Some(value)
+}
+/// Returns the target file and object paths of the HDF5 external link `name`
+fn external_link(location: &hdf5::Location, name: &str) -> Option<(String, String)> {
+    use std::ffi::CStr;
+    let value = link_value(location, name)?;
+    let mut flags = 0u32;
+    let mut file = std::ptr::null();
+    let mut object = std::ptr::null();
+    // fails if `name` is not an external link
+    if hdf5::sync::sync(|| unsafe {
+        hdf5_sys::h5l::H5Lunpack_elink_val(
+            value.as_ptr() as *const _,
+            value.len(),
+            &mut flags,
+            &mut file,
+            &mut object,
+        )
+    }) < 0
+    {
+        return None;
+    }
+    // the paths point into `value`
+    unsafe {
+        Some((
+            CStr::from_ptr(file).to_string_lossy().into_owned(),
+            CStr::from_ptr(object).to_string_lossy().into_owned(),
+        ))
+    }
+}
+/// Returns the target path of the HDF5 soft link `name`
+fn soft_link(location: &hdf5::Location, name: &str) -> Option<String> {
+    if external_link(location, name).is_some() {
+        return None;
+    }
+    let value = link_value(location, name)?;
+    let end = value.iter().position(|&b| b == 0).unwrap_or(value.len());
+    Some(String::from_utf8_lossy(&value[..end]).into_owned())
+}
+/// Reads the struct field names of the `MATLAB_fields` attribute
+///
+/// Matlab stores each name as a variable length array of characters,
+/// other writers may store the names as fixed or variable length strings
+fn field_names(attr: &hdf5::Attribute) -> Result<Vec<String>> {
+    use hdf5::types::{FixedUnicode, TypeDescriptor, VarLenArray, VarLenAscii, VarLenUnicode};
+    let names = match attr.dtype()?.to_descriptor()? {
+        TypeDescriptor::VarLenArray(_) => attr
+            .read_raw::<VarLenArray<FixedAscii<1>>>()?
+            .into_iter()
+            .map(|v| v.iter().map(|x| x.as_str()).collect())
+            .collect(),
+        TypeDescriptor::VarLenAscii => attr
+            .read_raw::<VarLenAscii>()?
+            .iter()
+            .map(|x| x.as_str().to_string())
+            .collect(),
+        TypeDescriptor::VarLenUnicode => attr
+            .read_raw::<VarLenUnicode>()?
+            .iter()
+            .map(|x| x.as_str().to_string())
+            .collect(),
+        TypeDescriptor::FixedUnicode(_) => attr
+            .read_raw::<FixedUnicode<256>>()?
+            .iter()
+            .map(|x| x.as_str().trim_end().to_string())
+            .collect(),
+        _ => attr
+            .read_raw::<FixedAscii<256>>()?
+            .iter()
+            .map(|x| x.as_str().trim_end().to_string())
+            .collect(),
+    };
+    Ok(names)
+}
+/// Returns the Matlab shape of the HDF5 `shape`, i.e. the reversed shape with at least 2 dimensions
+fn matlab_shape(shape: &[usize]) -> Vec<usize> {
+    let mut shape = shape.to_vec();
+    shape.resize(shape.len().max(2), 1);
+    shape.reverse();
+    shape
+}
+/// Returns [Error::Object] for a Matlab object with a `MATLAB_object_decode` attribute
+fn object_error(location: &hdf5::Location, name: &str) -> Option<Error> {
+    let decode = location
+        .attr("MATLAB_object_decode")
+        .ok()?
+        .read_scalar::<i32>()
+        .ok()?;
+    Some(Error::Object {
+        name: name.to_string(),
+        class: matlab_class(location).unwrap_or_default(),
+        decode,
+    })
+}
+/// Returns the error for a Matlab variable stored as a group read as an array
+fn wrong_reader(group: &hdf5::Group, name: &str) -> Error {
+    let reader = |kind: &str, reader: &str| Error::Reader {
+        name: name.to_string(),
+        kind: kind.to_string(),
+        reader: reader.to_string(),
+    };
+    if let Some(e) = object_error(group, name) {
+        return e;
+    }
+    let class = matlab_class(group).ok();
+    if group.attr("MATLAB_sparse").is_ok() {
+        if class.as_deref() == Some("logical") {
+            reader("sparse logical", "sparse_logical")
+        } else {
+            reader("sparse", "sparse")
+        }
+    } else {
+        match class.as_deref() {
+            Some("struct") => reader("a struct", "structure"),
+            Some(class) => reader(&format!("a {} object", class), "read"),
+            None => Error::Dataset(name.to_string()),
+        }
+    }
+}
+/// Returns the `value` dataset or the single dataset of a group without Matlab class
+fn wrapped_dataset(group: &hdf5::Group) -> Option<hdf5::Dataset> {
+    if group.attr("MATLAB_class").is_ok() {
+        return None;
+    }
+    if let Ok(dataset) = group.dataset("value") {
+        return Some(dataset);
+    }
+    match group.member_names().ok()?.as_slice() {
+        [member] => group.dataset(member).ok(),
+        _ => None,
+    }
+}
+/// Decodes a Matlab char array (UTF-16 code units)
+///
+/// The rows of a char matrix are decoded as [char_rows] does and joined by newlines,
+/// returns [Error::Shape] for a char array with more than 2 dimensions
+fn char_string(dataset: &hdf5::Dataset) -> Result<String> {
+    let shape = dataset.shape();
+    let data = dataset.read_raw::<u16>()?;
+    match shape[..] {
+        [_, n_row] if n_row > 1 => Ok(char_rows(&data, &shape).join("\n")),
+        _ if shape.len() > 2 => Err(Error::Shape {
+            expected: "a char row vector or matrix".to_string(),
+            found: matlab_shape(&shape),
+        }),
+        _ => Ok(std::char::decode_utf16(data)
+            .map(|c| c.unwrap_or(std::char::REPLACEMENT_CHARACTER))
+            .collect()),
+    }
+}
+/// Returns the number of elements of an array of the given HDF5 shape
+///
+/// Returns [Error::Shape] if the number of elements overflows `usize`
+fn element_count(shape: &[usize]) -> Result<usize> {
+    shape
+        .iter()
+        .try_fold(1usize, |count, &n| count.checked_mul(n))
+        .ok_or_else(|| Error::Shape {
+            expected: format!("at most {} elements", usize::MAX),
+            found: matlab_shape(shape),
+        })
+}
+/// Returns the product of `factors`
+///
+/// Returns [Error::Overflow] for the first factor that makes the product overflow `usize`
+fn checked_product(factors: &[usize]) -> Result<usize> {
+    factors
+        .iter()
+        .enumerate()
+        .try_fold(1usize, |product, (index, &n)| {
+            product.checked_mul(n).ok_or_else(|| Error::Overflow {
+                index,
+                value: n.to_string(),
+            })
+        })
+}
+/// Returns the column-major strides of an array with the Matlab `shape`
+fn column_major_strides(shape: &[usize]) -> Result<Vec<usize>> {
+    (0..shape.len())
+        .map(|k| checked_product(&shape[..k]))
+        .collect()
+}
+/// Decodes the rows of a Matlab char matrix with the HDF5 `shape`, trimming the trailing spaces
+fn char_rows(data: &[u16], shape: &[usize]) -> Vec<String> {
+    let n_row = shape.get(1).copied().unwrap_or(1);
+    (0..n_row)
+        .map(|i| {
+            std::char::decode_utf16(data.iter().skip(i).step_by(n_row).cloned())
+                .map(|c| c.unwrap_or(std::char::REPLACEMENT_CHARACTER))
+                .collect::<String>()
+                .trim_end_matches(' ')
+                .to_string()
+        })
+        .collect()
+}
+/// Reads the single element of a Matlab 1x1 array
+fn scalar_value<T: hdf5::H5Type>(dataset: &hdf5::Dataset) -> Result<T> {
+    let mut data = dataset.read_raw::<T>()?;
+    if data.len() == 1 {
+        Ok(data.remove(0))
+    } else {
+        Err(Error::Shape {
+            expected: "a 1x1 array".to_string(),
+            found: matlab_shape(&dataset.shape()),
+        })
+    }
+}
+
+/// Creates a Rust `Vec` from a Matlab array, column wise
+impl<T> From<MatVar<T>> for Vec<T> {
+    fn from(var: MatVar<T>) -> Self {
+        var.raw()
+    }
+}
+#[cfg(feature = "arrow")]
+impl<T: Clone> MatVar<T> {
+    /// Creates an Arrow record batch with a column `A` of Arrow array type for each column of a Matlab 2D array,
+    /// the columns being named after their 1-based index
+    ///
+    /// Returns [Error::Shape] if the array dimension is greater than 2
+    pub fn into_record_batch<A>(self) -> Result<arrow::record_batch::RecordBatch>
+    where
+        A: arrow::array::Array + From<Vec<T>> + 'static,
+    {
+        self.check_2d()?;
+        let n_row = self.n_row();
+        let columns = (0..self.n_column()).map(|j| {
+            let column: arrow::array::ArrayRef =
+                std::sync::Arc::new(A::from(self.data[j * n_row..(j + 1) * n_row].to_vec()));
+            ((j + 1).to_string(), column)
+        });
+        Ok(arrow::record_batch::RecordBatch::try_from_iter(columns)?)
+    }
+}
+/// Implements the conversion of Matlab vectors of `$t` into Arrow arrays of type `$array`
+macro_rules! arrow_array {
+    ($($array:ident: $t:ty),*) => {
+        $(
+            #[cfg(feature = "arrow")]
+            /// Creates an Arrow array from a Matlab row or column vector
+            ///
+            /// Returns [Error::Shape] if the array is not a vector
+            impl std::convert::TryFrom<MatVar<$t>> for arrow::array::$array {
+                type Error = Error;
+                fn try_from(var: MatVar<$t>) -> Result<Self> {
+                    check_vector(&var.shape)?;
+                    Ok(Self::from(var.data))
+                }
+            }
+        )*
+    };
+}
+arrow_array!(
+    Float64Array: f64,
+    Float32Array: f32,
+    Int64Array: i64,
+    Int32Array: i32,
+    Int16Array: i16,
+    Int8Array: i8,
+    UInt64Array: u64,
+    UInt32Array: u32,
+    UInt16Array: u16,
+    UInt8Array: u8
+);
+#[cfg(feature = "json")]
+impl MatStruct {
+    /// Converts the struct into a JSON object, reading the nested structs recursively
+    ///
+    /// Numeric and logical fields are numbers and booleans, or arrays of them with a nested array per Matlab row
+    /// for a matrix, char fields are strings and the fields of any other class are the string `<class>`
+    pub fn to_json(&self) -> Result<serde_json::Value> {
+        let mut object = serde_json::Map::new();
+        for name in &self.field_names {
+            let info = self.field_info(name)?;
+            let is_real = info.dtype.as_ref().is_some_and(|dtype| !is_complex(dtype));
+            let value = match self.empty_field(name, &info.class)? {
+                Some(MatValue::Char(text)) => text.into(),
+                Some(_) => serde_json::Value::Array(Vec::new()),
+                None => match info.class.as_str() {
+                    class if NUMERIC_CLASSES.contains(&class) && is_real => {
+                        json_array(self.field::<f64>(name)?)
+                    }
+                    "logical" => {
+                        let var = self.field::<u8>(name)?;
+                        json_array(MatVar {
+                            name: var.name,
+                            shape: var.shape,
+                            data: var.data.into_iter().map(|x| x != 0).collect(),
+                        })
+                    }
+                    "char" => self.string_field(name)?.into(),
+                    "struct" => self.substruct(name)?.to_json()?,
+                    class => format!("<{}>", class).into(),
+                },
+            };
+            object.insert(name.clone(), value);
+        }
+        Ok(serde_json::Value::Object(object))
+    }
+}
+#[cfg(feature = "json")]
+/// Converts a Matlab array into a JSON value: a scalar, an array for a vector,
+/// an array of Matlab rows for a matrix and a flat array otherwise
+fn json_array<T: Clone + Into<serde_json::Value>>(var: MatVar<T>) -> serde_json::Value {
+    match var.dims() {
+        Ok((1, 1)) => var.data[0].clone().into(),
+        Ok((n_row, n_column)) if n_row > 1 && n_column > 1 => (0..n_row)
+            .map(|i| {
+                (0..n_column)
+                    .map(|j| var.data[j * n_row + i].clone())
+                    .collect::<Vec<T>>()
+                    .into()
+            })
+            .collect::<Vec<serde_json::Value>>()
+            .into(),
+        _ => var.data.into(),
+    }
+}
+#[cfg(feature = "nalgebra")]
+impl<T: 'static + std::marker::Copy + std::cmp::PartialEq + std::fmt::Debug> MatVar<T> {
+    /// Creates a nalgebra matrix from a Matlab 2D array
+    ///
+    /// The singleton dimensions of an array with more than 2 dimensions are removed first,
+    /// returns [Error::Shape] with the Matlab shape of the array if more than 2 dimensions remain
+    pub fn into_matrix(self) -> Result<nalgebra::DMatrix<T>> {
+        let var = self.squeeze();
+        if var.shape.len() > 2 {
+            Err(Error::Shape {
+                expected: "a 2D array, use MatVar::into_slices or File::read_into_ndarray instead"
+                    .to_string(),
+                found: var.matlab_shape(),
+            })
+        } else {
+            Ok(nalgebra::DMatrix::from_column_slice(
+                var.n_row(),
+                var.n_column(),
+                &var.data,
+            ))
+        }
+    }
+    /// Splits a Matlab 3D array along its last dimension into nalgebra matrices
+    ///
+    /// Returns [Error::Shape] if the array dimension is not 3
+    pub fn into_slices(self) -> Result<Vec<nalgebra::DMatrix<T>>> {
+        match self.shape[..] {
+            [n_slice, n_column, n_row] => self.slices(0, n_slice, n_row, n_column),
+            _ => Err(Error::Shape {
+                expected: "a 3D array".to_string(),
+                found: self.matlab_shape(),
+            }),
+        }
+    }
+    /// Splits a Matlab 4D array along its last 2 dimensions into nalgebra matrices,
+    /// indexed by the 4th then the 3rd dimension
+    ///
+    /// Returns [Error::Shape] if the array dimension is not 4
+    pub fn into_slices_4d(self) -> Result<Vec<Vec<nalgebra::DMatrix<T>>>> {
+        match self.shape[..] {
+            [n_block, n_slice, n_column, n_row] => (0..n_block)
+                .map(|k| self.slices(k * n_slice, n_slice, n_row, n_column))
+                .collect(),
+            _ => Err(Error::Shape {
+                expected: "a 4D array".to_string(),
+                found: self.matlab_shape(),
+            }),
+        }
+    }
+    /// Returns `n_slice` matrices starting from the matrix `first`
+    fn slices(
+        &self,
+        first: usize,
+        n_slice: usize,
+        n_row: usize,
+        n_column: usize,
+    ) -> Result<Vec<nalgebra::DMatrix<T>>> {
+        let n = checked_product(&[n_row, n_column])?;
+        Ok((first..first + n_slice)
+            .map(|k| {
+                nalgebra::DMatrix::from_column_slice(
+                    n_row,
+                    n_column,
+                    &self.data[k * n..(k + 1) * n],
+                )
+            })
+            .collect())
+    }
+    /// Creates a nalgebra matrix from a Matlab 2D array, returning it with the array name
+    ///
+    /// Returns [Error::Shape] if the array has more than 2 non-singleton dimensions
+    pub fn into_labeled_matrix(mut self) -> Result<(String, nalgebra::DMatrix<T>)> {
+        let name = std::mem::take(&mut self.name);
+        Ok((name, self.into_matrix()?))
+    }
+}
+#[cfg(feature = "nalgebra")]
+impl MatVar<f64> {
+    /// Creates a nalgebra matrix from a Matlab 2D array, see [MatVar::into_matrix],
+    /// handling the NaN and infinite elements according to `policy`
+    pub fn into_matrix_checked(mut self, policy: NanPolicy<f64>) -> Result<nalgebra::DMatrix<f64>> {
+        match policy {
+            NanPolicy::Allow => (),
+            NanPolicy::Error => {
+                let indices: Vec<usize> = self
+                    .data
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, x)| !x.is_finite())
+                    .map(|(k, _)| k)
+                    .collect();
+                if !indices.is_empty() {
+                    return Err(Error::NonFinite {
+                        name: self.name,
+                        indices,
+                    });
+                }
+            }
+            NanPolicy::Replace(value) => self
+                .data
+                .iter_mut()
+                .filter(|x| !x.is_finite())
+                .for_each(|x| *x = value),
+        }
+        self.into_matrix()
+    }
+    /// Returns the largest absolute difference with the elements of a nalgebra matrix,
+    /// `None` if the shapes differ
+    ///
+    /// The difference is NaN if any element of either array is NaN
+    pub fn max_abs_diff(&self, other: &nalgebra::DMatrix<f64>) -> Option<f64> {
+        if self.shape.len() > 2 || other.shape() != (self.n_row(), self.n_column()) {
+            return None;
+        }
+        Some(
+            self.data
+                .iter()
+                .zip(other.iter())
+                .map(|(a, b)| (a - b).abs())
+                .fold(0., |max, diff| {
+                    if max.is_nan() || diff.is_nan() {
+                        f64::NAN
+                    } else {
+                        max.max(diff)
+                    }
+                }),
+        )
+    }
+    /// Checks that a nalgebra matrix has the same shape and elements within `tol`
+    pub fn approx_eq_matrix(&self, other: &nalgebra::DMatrix<f64>, tol: f64) -> bool {
+        self.max_abs_diff(other).is_some_and(|diff| diff <= tol)
+    }
+}
+#[cfg(feature = "nalgebra")]
+/// Creates a nalgebra matrix from a Matlab 2D array
+///
+/// Panics if the array has more than 2 non-singleton dimensions, use [MatVar::into_matrix] instead to get an [Error]
+impl<T: 'static + std::marker::Copy + std::cmp::PartialEq + std::fmt::Debug> From<MatVar<T>>
+    for nalgebra::Matrix<
+        T,
+        nalgebra::Dynamic,
+        nalgebra::Dynamic,
+        nalgebra::VecStorage<T, nalgebra::Dynamic, nalgebra::Dynamic>,
+    >
+{
+    fn from(var: MatVar<T>) -> Self {
+        match var.into_matrix() {
+            Ok(matrix) => matrix,
+            Err(e) => panic!("{}", e),
+        }
+    }
+}
+#[cfg(feature = "nalgebra")]
+/// Creates a nalgebra column vector from a Matlab row or column vector
+///
+/// Returns [Error::Shape] if the array is not a vector
+impl<T: 'static + std::marker::Copy + std::cmp::PartialEq + std::fmt::Debug>
+    std::convert::TryFrom<MatVar<T>> for nalgebra::DVector<T>
+{
+    type Error = Error;
+    fn try_from(var: MatVar<T>) -> Result<Self> {
+        check_vector(&var.shape)?;
+        Ok(nalgebra::DVector::from_vec(var.data))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    /// Path of a file in the temporary directory, unique to the test process and removed when dropped
+    struct TempPath(std::path::PathBuf);
+    impl TempPath {
+        fn new(name: &str) -> Self {
+            Self(std::env::temp_dir().join(format!("mat73_{}_{}.mat", std::process::id(), name)))
+        }
+    }
+    impl std::ops::Deref for TempPath {
+        type Target = std::path::Path;
+        fn deref(&self) -> &std::path::Path {
+            &self.0
+        }
+    }
+    impl AsRef<std::path::Path> for TempPath {
+        fn as_ref(&self) -> &std::path::Path {
+            &self.0
+        }
+    }
+    impl Drop for TempPath {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+    /// Creates the HDF5 file `name` in the temporary directory
+    fn fixture(name: &str) -> (TempPath, hdf5::File) {
+        let path = TempPath::new(name);
+        let h5 = hdf5::File::create(&path).unwrap();
+        (path, h5)
+    }
+    /// Writes the `MATLAB_class` attribute
+    fn write_class(location: &hdf5::Location, class: &str) {
+        location
+            .new_attr::<FixedAscii<32>>()
+            .shape(())
+            .create("MATLAB_class")
+            .unwrap()
+            .write_scalar(&FixedAscii::<32>::from_ascii(class).unwrap())
+            .unwrap();
+    }
+    /// Writes a scalar attribute
+    fn write_attr<T: hdf5::H5Type>(location: &hdf5::Location, name: &str, value: T) {
+        location
+            .new_attr::<T>()
+            .shape(())
+            .create(name)
+            .unwrap()
+            .write_scalar(&value)
+            .unwrap();
+    }
+    /// Writes a Matlab array of the given class and Matlab shape, the data being in Matlab column-major order
+    fn write_array<T: hdf5::H5Type>(
+        group: &hdf5::Group,
+        name: &str,
+        class: &str,
+        shape: &[usize],
+        data: &[T],
+    ) -> hdf5::Dataset {
+        let dataset = group
+            .new_dataset::<T>()
+            .shape(shape.iter().rev().copied().collect::<Vec<usize>>())
+            .create(name)
+            .unwrap();
+        dataset.write_raw(data).unwrap();
+        write_class(&dataset, class);
+        dataset
+    }
+    /// Writes an empty Matlab array of the given class, stored as its dimensions with the `MATLAB_empty` attribute
+    fn write_empty(group: &hdf5::Group, name: &str, class: &str, shape: &[u64]) {
+        let dataset = group
+            .new_dataset::<u64>()
+            .shape(shape.len())
+            .create(name)
+            .unwrap();
+        dataset.write_raw(shape).unwrap();
+        write_class(&dataset, class);
+        write_attr(&dataset, "MATLAB_empty", 1u8);
+    }
+    /// Writes a Matlab struct group declaring `fields` in its `MATLAB_fields` attribute
+    fn write_struct(group: &hdf5::Group, name: &str, fields: &[&str]) -> hdf5::Group {
+        use hdf5::types::VarLenArray;
+        let struct_group = group.create_group(name).unwrap();
+        write_class(&struct_group, "struct");
+        let names: Vec<VarLenArray<FixedAscii<1>>> = fields
+            .iter()
+            .map(|field| {
+                let chars: Vec<FixedAscii<1>> = field
+                    .bytes()
+                    .map(|c| FixedAscii::<1>::from_ascii(&[c]).unwrap())
+                    .collect();
+                VarLenArray::from_slice(&chars)
+            })
+            .collect();
+        struct_group
+            .new_attr::<VarLenArray<FixedAscii<1>>>()
+            .shape(names.len())
+            .create("MATLAB_fields")
+            .unwrap()
+            .write_raw(&names)
+            .unwrap();
+        struct_group
+    }
+    /// Matlab complex double element, a HDF5 compound type with the `real` and `imag` members
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    #[repr(C)]
+    struct Complex {
+        real: f64,
+        imag: f64,
+    }
+    unsafe impl hdf5::H5Type for Complex {
+        fn type_descriptor() -> hdf5::types::TypeDescriptor {
+            let member = |name: &str, offset, index| hdf5::types::CompoundField {
+                name: name.to_string(),
+                ty: f64::type_descriptor(),
+                offset,
+                index,
+            };
+            hdf5::types::TypeDescriptor::Compound(hdf5::types::CompoundType {
+                fields: vec![member("real", 0, 0), member("imag", 8, 1)],
+                size: std::mem::size_of::<Complex>(),
+            })
+        }
+    }
+    /// Writes a Matlab sparse matrix: the `data` elements with their `ir` row indices and `jc` column pointers
+    fn write_sparse<T: hdf5::H5Type>(
+        group: &hdf5::Group,
+        name: &str,
+        class: &str,
+        n_row: u64,
+        jc: &[u64],
+        ir: &[u64],
+        data: &[T],
+    ) {
+        let sparse_group = group.create_group(name).unwrap();
+        write_class(&sparse_group, class);
+        write_attr(&sparse_group, "MATLAB_sparse", n_row);
+        for (member, index) in [("jc", jc), ("ir", ir)] {
+            let dataset = sparse_group
+                .new_dataset::<u64>()
+                .shape(index.len())
+                .create(member)
+                .unwrap();
+            dataset.write_raw(index).unwrap();
+        }
+        let dataset = sparse_group
+            .new_dataset::<T>()
+            .shape(data.len())
+            .create("data")
+            .unwrap();
+        dataset.write_raw(data).unwrap();
+    }
+    /// Writes a Matlab MCOS object: a `uint32` dataset with the `MATLAB_object_decode` attribute
+    fn write_object(group: &hdf5::Group, name: &str, class: &str) {
+        let dataset = write_array::<u32>(group, name, class, &[6, 1], &[0xdd000000, 2, 1, 1, 1, 1]);
+        write_attr(&dataset, "MATLAB_object_decode", 3i32);
+    }
+    /// Creates the external link `name` to the `object` of the HDF5 file `file`
+    fn write_external_link(location: &hdf5::Location, name: &str, file: &str, object: &str) {
+        let (file, object, name) = (
+            std::ffi::CString::new(file).unwrap(),
+            std::ffi::CString::new(object).unwrap(),
+            std::ffi::CString::new(name).unwrap(),
+        );
+        assert!(
+            hdf5::sync::sync(|| unsafe {
+                hdf5_sys::h5l::H5Lcreate_external(
+                    file.as_ptr(),
+                    object.as_ptr(),
+                    location.id(),
+                    name.as_ptr(),
+                    hdf5_sys::h5p::H5P_DEFAULT,
+                    hdf5_sys::h5p::H5P_DEFAULT,
+                )
+            }) >= 0
+        );
+    }
+    #[test]
+    fn array_to_vec() {
+        let file = "examples/arrays.mat";
+        let mat_file = File::new(file).unwrap();
+        let var: Vec<f64> = mat_file.array("q").unwrap().into();
+        assert_eq!(var, vec![1.0, 4.0, 2.0, 5.0, 3.0, 6.0])
+    }
+    #[test]
+    fn array_to_matrix() {
+        let file = "examples/arrays.mat";
+        let mat_file = File::new(file).unwrap();
+        let var: nalgebra::DMatrix<f64> = mat_file.array("w").unwrap().into();
+        assert_eq!(
+            var,
+            nalgebra::DMatrix::from_column_slice(3, 2, &[0., -6., -2., 1., 56., 1.])
+        )
+    }
+    #[test]
+    fn exact_type() {
+        let file = "examples/arrays.mat";
+        let mat_file = File::new(file).unwrap();
+        assert!(mat_file.is_exact_type::<f64>("q").unwrap());
+        assert!(!mat_file.is_exact_type::<f32>("q").unwrap());
+    }
+    #[cfg(feature = "nalgebra")]
+    #[test]
+    fn array_into_matrix() {
+        let var = MatVar {
+            name: "a".to_string(),
+            shape: vec![2, 2, 2],
+            data: vec![0f64; 8],
+        };
+        assert!(matches!(var.into_matrix(), Err(Error::Shape { .. })));
+    }
+    #[test]
+    fn iter_variables() {
+        let file = "examples/arrays.mat";
+        let mat_file = File::new(file).unwrap();
+        let names = mat_file.variables().unwrap();
+        assert!(names.iter().any(|name| name == "q"));
+        assert!(names.iter().any(|name| name == "w"));
+        assert_eq!(mat_file.iter().count(), names.len());
+        assert!(mat_file
+            .iter()
+            .all(|var| matches!(var, Ok((_, MatValue::Numeric(_))))));
+    }
+    #[test]
+    fn one_dimension_rows_columns() {
+        let var = MatVar {
+            name: "v".to_string(),
+            shape: vec![3],
+            data: vec![1f64, 2., 3.],
+        };
+        assert_eq!(var.n_row(), 1);
+        assert_eq!(var.n_column(), 3);
+    }
+    #[test]
+    fn reshape() {
+        let var = MatVar {
+            name: "v".to_string(),
+            shape: vec![6],
+            data: vec![1f64, 2., 3., 4., 5., 6.],
+        };
+        let var = var.reshape(vec![3, 2]).unwrap();
+        assert_eq!(var.shape(), &[2, 3]);
+        assert_eq!(var.matlab_shape(), vec![3, 2]);
+        assert!(matches!(var.reshape(vec![4, 2]), Err(Error::Shape { .. })));
+    }
+    #[test]
+    fn char_matrix_rows() {
+        let data: Vec<u16> = "adb c ".encode_utf16().collect();
+        assert_eq!(char_rows(&data, &[3, 2]).join("\n"), "abc\nd");
+    }
+    #[test]
+    fn element_count_overflow() {
+        assert_eq!(element_count(&[3, 2]).unwrap(), 6);
+        assert!(matches!(
+            element_count(&[usize::MAX, 2]),
+            Err(Error::Shape { .. })
+        ));
+    }
+    #[cfg(feature = "nalgebra")]
+    #[test]
+    fn array_to_vector() {
+        use std::convert::TryFrom;
+        let file = "examples/arrays.mat";
+        let mat_file = File::new(file).unwrap();
+        assert!(matches!(
+            nalgebra::DVector::<f64>::try_from(mat_file.array::<f64>("w").unwrap()),
+            Err(Error::Shape { .. })
+        ));
+        let var = MatVar {
+            name: "v".to_string(),
+            shape: vec![3, 1],
+            data: vec![1f64, 2., 3.],
+        };
+        assert_eq!(
+            nalgebra::DVector::try_from(var).unwrap(),
+            nalgebra::DVector::from_vec(vec![1f64, 2., 3.])
+        );
+    }
+    #[test]
+    fn open_from_bytes() {
+        let bytes = std::fs::read("examples/arrays.mat").unwrap();
+        let mat_file = File::from_bytes(&bytes).unwrap();
+        let var: Vec<f64> = mat_file.array("q").unwrap().into();
+        assert_eq!(var, vec![1.0, 4.0, 2.0, 5.0, 3.0, 6.0])
+    }
+    #[test]
+    fn hdf5_error_without_source() {
+        let e = Error::from(hdf5::Error::from("Setting the HDF5 file image failed"));
+        assert!(e.to_string().contains("Setting the HDF5 file image failed"));
+    }
+    #[cfg(feature = "compressed")]
+    #[test]
+    fn open_compressed() {
+        use std::io::Write;
+        let bytes = std::fs::read("examples/arrays.mat").unwrap();
+        let mut gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gzip.write_all(&bytes).unwrap();
+        let gzip_path = TempPath::new("open_compressed_gz");
+        std::fs::write(&gzip_path, gzip.finish().unwrap()).unwrap();
+        let zstd_path = TempPath::new("open_compressed_zst");
+        std::fs::write(&zstd_path, zstd::encode_all(bytes.as_slice(), 0).unwrap()).unwrap();
+        for path in [&gzip_path, &zstd_path] {
+            let mat_file = File::from_compressed(path).unwrap();
+            assert_eq!(
+                mat_file.array::<f64>("q").unwrap().data,
+                vec![1.0, 4.0, 2.0, 5.0, 3.0, 6.0]
+            );
+        }
+        let mat_file = File::from_compressed("examples/arrays.mat").unwrap();
+        assert!(mat_file.array::<f64>("q").is_ok());
+    }
+    #[test]
+    fn array_progress() {
+        let file = "examples/arrays.mat";
+        let mat_file = File::new(file).unwrap();
+        let mut progress = (0, 0);
+        let var: Vec<f64> = mat_file
+            .array_with_progress("q", |read, total| progress = (read, total))
+            .unwrap()
+            .into();
+        assert_eq!(var, vec![1.0, 4.0, 2.0, 5.0, 3.0, 6.0]);
+        assert_eq!(progress, (48, 48));
+    }
+    #[test]
+    fn optional_array() {
+        let file = "examples/arrays.mat";
+        let mat_file = File::new(file).unwrap();
+        assert!(mat_file.array_optional::<f64>("q").unwrap().is_some());
+        assert!(mat_file.array_optional::<f64>("none").unwrap().is_none());
+    }
+    #[test]
+    fn little_endian() {
+        let file = "examples/arrays.mat";
+        let mat_file = File::new(file).unwrap();
+        assert_eq!(
+            mat_file.byte_order("q").unwrap(),
+            hdf5::ByteOrder::LittleEndian
+        );
+    }
+    #[test]
+    fn big_endian() {
+        use hdf5_sys::{h5d, h5p::H5P_DEFAULT, h5s, h5t};
+        let (path, h5) = fixture("big_endian");
+        let data = [1f64, -2.5, 3e10];
+        // the hdf5 crate only writes native types
+        hdf5::sync::sync(|| unsafe {
+            let dims: [hdf5_sys::h5::hsize_t; 2] = [3, 1];
+            let space = h5s::H5Screate_simple(2, dims.as_ptr(), std::ptr::null());
+            let name = std::ffi::CString::new("b").unwrap();
+            let dataset = h5d::H5Dcreate2(
+                h5.id(),
+                name.as_ptr(),
+                *h5t::H5T_IEEE_F64BE,
+                space,
+                H5P_DEFAULT,
+                H5P_DEFAULT,
+                H5P_DEFAULT,
+            );
+            assert!(dataset >= 0);
+            assert!(
+                h5d::H5Dwrite(
+                    dataset,
+                    *h5t::H5T_NATIVE_DOUBLE,
+                    h5s::H5S_ALL,
+                    h5s::H5S_ALL,
+                    H5P_DEFAULT,
+                    data.as_ptr() as *const _,
+                ) >= 0
+            );
+            h5d::H5Dclose(dataset);
+            h5s::H5Sclose(space);
+        });
+        write_class(&h5.dataset("b").unwrap(), "double");
+        let mat_file = File::new(&path).unwrap();
+        assert_eq!(
+            mat_file.byte_order("b").unwrap(),
+            hdf5::ByteOrder::BigEndian
+        );
+        let var = mat_file.array::<f64>("b").unwrap();
+        assert_eq!(var.shape(), &[3, 1]);
+        assert_eq!(Vec::from(var), data.to_vec());
+    }
+    #[test]
+    fn diagonal_submatrix() {
+        let file = "examples/arrays.mat";
+        let mat_file = File::new(file).unwrap();
+        let var = mat_file.array::<f64>("q").unwrap();
+        assert!(matches!(var.diagonal(), Err(Error::Shape { .. })));
+        let block = var.submatrix(0..2, 1..3).unwrap();
+        assert_eq!(block.shape(), &[2, 2]);
+        assert_eq!(block.diagonal().unwrap(), vec![2., 6.]);
+        assert!(matches!(
+            var.submatrix(0..3, 0..1),
+            Err(Error::Shape { .. })
+        ));
+    }
+    #[test]
+    fn shaped_array() {
+        let file = "examples/arrays.mat";
+        let mat_file = File::new(file).unwrap();
+        assert!(mat_file.array_shaped::<f64>("q", &[2, 3]).is_ok());
+        assert!(matches!(
+            mat_file.array_shaped::<f64>("q", &[3, 2]),
+            Err(Error::Shape { .. })
+        ));
+    }
+    #[cfg(feature = "ndarray")]
+    #[test]
+    fn vector_into_array1() {
+        let row = MatVar {
+            name: "r".to_string(),
+            shape: vec![3, 1],
+            data: vec![1f64, 2., 3.],
+        };
+        assert_eq!(row.into_array1().unwrap().into_raw_vec(), vec![1., 2., 3.]);
+        let column = MatVar {
+            name: "c".to_string(),
+            shape: vec![1, 3],
+            data: vec![1f64, 2., 3.],
+        };
+        assert_eq!(column.into_array1().unwrap().len(), 3);
+        let file = "examples/arrays.mat";
+        let mat_file = File::new(file).unwrap();
+        assert!(matches!(
+            mat_file.array::<f64>("q").unwrap().into_array1(),
+            Err(Error::Shape { .. })
+        ));
+    }
+    #[test]
+    fn sparse_triplets() {
+        let sparse = MatSparse {
+            name: "s".to_string(),
+            n_row: 3,
+            column_pointers: vec![0, 1, 1, 3],
+            row_indices: vec![2, 0, 1],
+            values: vec![true, true, false],
+        };
+        assert_eq!(sparse.n_column(), 3);
+        assert_eq!(
+            sparse.triplets().collect::<Vec<_>>(),
+            vec![(2, 0, &true), (0, 2, &true), (1, 2, &false)]
+        );
+    }
+    #[test]
+    fn sparse_matrix() {
+        let (path, h5) = fixture("sparse_matrix");
+        // [0 0 5; 0 0 6; 4 0 0]
+        write_sparse(
+            &h5,
+            "s",
+            "double",
+            3,
+            &[0, 1, 1, 3],
+            &[2, 0, 1],
+            &[4., 5., 6.],
+        );
+        write_sparse(
+            &h5,
+            "l",
+            "logical",
+            3,
+            &[0, 1, 1, 3],
+            &[2, 0, 1],
+            &[1u8, 0, 1],
+        );
+        write_sparse(
+            &h5,
+            "bad",
+            "double",
+            3,
+            &[0, 1, 5],
+            &[2, 0, 1],
+            &[4., 5., 6.],
+        );
+        let mat_file = File::new(&path).unwrap();
+        let sparse = mat_file.sparse::<f64>("s").unwrap();
+        assert_eq!((sparse.n_row(), sparse.n_column()), (3, 3));
+        assert_eq!(
+            sparse.triplets().collect::<Vec<_>>(),
+            vec![(2, 0, &4.), (0, 2, &5.), (1, 2, &6.)]
+        );
+        let sparse = mat_file.sparse_logical("l").unwrap();
+        assert_eq!(
+            sparse.triplets().collect::<Vec<_>>(),
+            vec![(2, 0, &true), (0, 2, &false), (1, 2, &true)]
+        );
+        assert!(matches!(
+            mat_file.sparse::<f64>("bad"),
+            Err(Error::Sparse(_))
+        ));
+    }
+    #[test]
+    fn sparse_complex_matrix() {
+        let (path, h5) = fixture("sparse_complex_matrix");
+        // [0 1-2i; 3i 0]
+        let data = [
+            Complex { real: 0., imag: 3. },
+            Complex {
+                real: 1.,
+                imag: -2.,
+            },
+        ];
+        write_sparse(&h5, "z", "double", 2, &[0, 1, 2], &[1, 0], &data);
+        let mat_file = File::new(&path).unwrap();
+        let sparse = mat_file.sparse_complex::<f64>("z").unwrap();
+        assert_eq!(
+            sparse.triplets().collect::<Vec<_>>(),
+            vec![(1, 0, &(0., 3.)), (0, 1, &(1., -2.))]
+        );
+        assert_eq!(mat_file.sparse::<Complex>("z").unwrap().values(), &data);
+    }
+    #[test]
+    fn complex_parts() {
+        let (path, h5) = fixture("complex_parts");
+        // [1+2i 3-4i]
+        let data = [
+            Complex { real: 1., imag: 2. },
+            Complex {
+                real: 3.,
+                imag: -4.,
+            },
+        ];
+        write_array(&h5, "z", "double", &[1, 2], &data);
+        let mat_file = File::new(&path).unwrap();
+        assert!(mat_file.is_complex("z").unwrap());
+        let real = mat_file.real_part::<f64>("z").unwrap();
+        assert_eq!(real.data, vec![1., 3.]);
+        assert_eq!(real.matlab_shape(), vec![1, 2]);
+        assert_eq!(mat_file.imag_part::<f64>("z").unwrap().data, vec![2., -4.]);
+    }
+    #[test]
+    fn array_size_bytes() {
+        let file = "examples/arrays.mat";
+        let mat_file = File::new(file).unwrap();
+        assert_eq!(mat_file.size_bytes("q").unwrap(), 6 * 8);
+    }
+    #[test]
+    fn missing_array() {
+        let file = "examples/arrays.mat";
+        let mat_file = File::new(file).unwrap();
+        let var = mat_file.array_with_missing("q", 4f64).unwrap();
+        assert_eq!(
+            var.data,
+            vec![Some(1.), None, Some(2.), Some(5.), Some(3.), Some(6.)]
+        );
+    }
+    #[test]
+    fn validate_schema() {
+        let file = "examples/arrays.mat";
+        let mat_file = File::new(file).unwrap();
+        let schema = [
+            VarSpec::new("q", "double").shape(&[2, 3]),
+            VarSpec::new("w", "double").shape(&[2, 3]),
+            VarSpec::new("missing", "double"),
+        ];
+        assert_eq!(
+            mat_file.validate(&schema).unwrap(),
+            vec![
+                SchemaError::Shape {
+                    name: "w".to_string(),
+                    expected: vec![2, 3],
+                    found: vec![3, 2],
+                },
+                SchemaError::Missing("missing".to_string()),
+            ]
+        );
+    }
+    #[test]
+    fn row_major_array() {
+        let file = "examples/arrays.mat";
+        let mat_file = File::new(file).unwrap();
+        let var = mat_file.array_row_major::<f64>("q").unwrap();
+        assert_eq!(var.shape(), &[2, 3]);
+        assert_eq!(var.data, vec![1., 2., 3., 4., 5., 6.]);
+    }
+    #[test]
+    fn shared_file_threads() {
+        let file = SharedFile::new("examples/arrays.mat").unwrap();
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let file = file.clone();
+                std::thread::spawn(move || file.array::<f64>("q").unwrap().data)
+            })
+            .collect();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), vec![1., 4., 2., 5., 3., 6.]);
+        }
+    }
+    #[test]
+    fn unscaled_array() {
+        let file = "examples/arrays.mat";
+        let mat_file = File::new(file).unwrap();
+        let var = mat_file.array_scaled("q").unwrap();
+        assert_eq!(var.data, vec![1., 4., 2., 5., 3., 6.]);
+    }
+    #[test]
+    fn array_2d() {
+        let file = "examples/arrays.mat";
+        let mat_file = File::new(file).unwrap();
+        let var = mat_file.array::<f64>("q").unwrap();
+        assert!(matches!(
+            var.reshape(vec![3, 2]).unwrap().into_array_2d::<2, 3>(),
+            Err(Error::Shape { .. })
+        ));
+        let var = mat_file.array::<f64>("q").unwrap();
+        assert_eq!(
+            var.into_array_2d::<2, 3>().unwrap(),
+            [[1., 2., 3.], [4., 5., 6.]]
+        );
+    }
+    #[cfg(feature = "nalgebra")]
+    #[test]
+    fn approx_matrix() {
+        let file = "examples/arrays.mat";
+        let mat_file = File::new(file).unwrap();
+        let var = mat_file.array::<f64>("q").unwrap();
+        let matrix = nalgebra::DMatrix::from_row_slice(2, 3, &[1., 2., 3., 4., 5., 6.001]);
+        assert!(var.approx_eq_matrix(&matrix, 1e-2));
+        assert!(!var.approx_eq_matrix(&matrix, 1e-4));
+        assert_eq!(var.max_abs_diff(&matrix.transpose()), None);
+        let matrix = nalgebra::DMatrix::from_row_slice(2, 3, &[1., 2., 3., 4., 5., f64::NAN]);
+        assert!(var.max_abs_diff(&matrix).unwrap().is_nan());
+        assert!(!var.approx_eq_matrix(&matrix, f64::INFINITY));
+    }
+    #[cfg(feature = "nalgebra")]
+    #[test]
+    fn read_matrix() {
+        let file = "examples/arrays.mat";
+        let mat_file = File::new(file).unwrap();
+        assert_eq!(
+            mat_file.matrix::<f32>("q").unwrap(),
+            nalgebra::DMatrix::from_row_slice(2, 3, &[1., 2., 3., 4., 5., 6.])
+        );
+    }
+    #[test]
+    fn squeeze() {
+        let var = MatVar {
+            name: "a".to_string(),
+            shape: vec![5, 1, 1],
+            data: vec![0f64; 5],
+        }
+        .squeeze();
+        assert_eq!(var.matlab_shape(), vec![5, 1]);
+        let var = MatVar {
+            name: "a".to_string(),
+            shape: vec![1, 5],
+            data: vec![0f64; 5],
+        }
+        .squeeze();
+        assert_eq!(var.matlab_shape(), vec![5, 1]);
+    }
+    #[test]
+    fn root_children() {
+        let file = "examples/arrays.mat";
+        let mat_file = File::new(file).unwrap();
+        let children = mat_file.children("/").unwrap();
+        assert!(children.contains(&(
+            "q".to_string(),
+            NodeKind::Dataset(Some("double".to_string()))
+        )));
+    }
+    #[test]
+    fn summary() {
+        let file = "examples/arrays.mat";
+        let mat_file = File::new(file).unwrap();
+        let summary = mat_file.summarize("q").unwrap();
+        assert!(summary.starts_with("q: double 2x3"));
+        assert!(summary.ends_with(", min 1 max 6 mean 3.5"));
+    }
+    #[test]
+    fn glob_pattern() {
+        let glob = Glob::new("trial_0[0-9]?*").unwrap();
+        assert!(glob.matches("trial_001"));
+        assert!(glob.matches("trial_0123"));
+        assert!(!glob.matches("trial_01"));
+        assert!(!glob.matches("trial_a01"));
+        assert!(Glob::new("[!q]").unwrap().matches("w"));
+        assert!(matches!(Glob::new("trial_[0-9"), Err(Error::Pattern(_))));
+    }
+    #[test]
+    fn matlab_dims() {
+        let var = MatVar {
+            name: "a".to_string(),
+            shape: vec![1, 3, 2],
+            data: vec![0f64; 6],
+        };
+        assert_eq!(var.dims().unwrap(), (2, 3));
+        let var = MatVar {
+            name: "a".to_string(),
+            shape: vec![2, 3, 2],
+            data: vec![0f64; 12],
+        };
+        assert!(matches!(var.dims(), Err(Error::Shape { .. })));
+    }
+    #[cfg(feature = "nalgebra")]
+    #[test]
+    fn singleton_into_matrix() {
+        let var = MatVar {
+            name: "a".to_string(),
+            shape: vec![2, 3, 1],
+            data: vec![1f64, 2., 3., 4., 5., 6.],
+        };
+        assert_eq!(
+            var.into_matrix().unwrap(),
+            nalgebra::DMatrix::from_column_slice(3, 2, &[1., 2., 3., 4., 5., 6.])
+        );
+    }
+    #[test]
+    fn downcast_array() {
+        let file = "examples/arrays.mat";
+        let mat_file = File::new(file).unwrap();
+        let var = mat_file.array_downcast_f32("q").unwrap();
+        assert_eq!(var.data, vec![1f32, 4., 2., 5., 3., 6.]);
+    }
+    #[test]
+    fn real_array() {
+        let file = "examples/arrays.mat";
+        let mat_file = File::new(file).unwrap();
+        assert!(!mat_file.is_complex("q").unwrap());
+    }
+    #[test]
+    fn builder_read() {
+        let file = "examples/arrays.mat";
+        let mat_file = File::new(file).unwrap();
+        let var = mat_file
+            .read_builder("q")
+            .as_type::<f32>()
+            .orientation(Orientation::RowMajor)
+            .expect_shape(&[2, 3])
+            .finish()
+            .unwrap();
+        assert_eq!(var.data, vec![1f32, 2., 3., 4., 5., 6.]);
+        assert!(matches!(
+            mat_file.read_builder("q").expect_shape(&[3, 2]).finish(),
+            Err(Error::Shape { .. })
+        ));
+    }
+    #[test]
+    fn imaginary_compound() {
+        use hdf5::types::{CompoundField, CompoundType, FloatSize, TypeDescriptor};
+        let compound = |names: &[&str]| {
+            TypeDescriptor::Compound(CompoundType {
+                fields: names
+                    .iter()
+                    .enumerate()
+                    .map(|(index, name)| CompoundField {
+                        name: name.to_string(),
+                        ty: TypeDescriptor::Float(FloatSize::U8),
+                        offset: 8 * index,
+                        index,
+                    })
+                    .collect(),
+                size: 8 * names.len(),
+            })
+        };
+        assert!(is_complex(&compound(&["real", "imag"])));
+        assert!(is_complex(&compound(&["imag"])));
+        assert!(!is_complex(&compound(&["real"])));
+        assert!(!is_complex(&compound(&["x", "imag"])));
+    }
+    #[test]
+    fn numeric_array() {
+        let file = "examples/arrays.mat";
+        let mat_file = File::new(file).unwrap();
+        assert!(matches!(
+            mat_file.read_numeric("q").unwrap(),
+            NumericArray::F64(_)
+        ));
+    }
+    #[cfg(feature = "nalgebra")]
+    #[test]
+    fn array_into_slices() {
+        let var = MatVar {
+            name: "a".to_string(),
+            shape: vec![2, 3, 2],
+            data: (0..12).map(|x| x as f64).collect(),
+        };
+        let slices = var.into_slices().unwrap();
+        assert_eq!(slices.len(), 2);
+        assert_eq!(
+            slices[1],
+            nalgebra::DMatrix::from_column_slice(2, 3, &[6., 7., 8., 9., 10., 11.])
+        );
+    }
+    #[test]
+    fn mat_superblock_version() {
+        let version = superblock_version(std::path::Path::new("examples/arrays.mat")).unwrap();
+        assert!(version <= max_superblock_version());
+    }
+    #[test]
+    fn newer_superblock() {
+        use hdf5::plist::LibraryVersion;
+        let (path, h5) = fixture("newer_superblock");
+        write_array::<f64>(&h5, "x", "double", &[1, 1], &[1.]);
+        h5.close().unwrap();
+        let mut bytes = std::fs::read(&path).unwrap();
+        assert_eq!(superblock_version(&path), Some(bytes[8]));
+        bytes[8] = max_superblock_version() + 1;
+        std::fs::write(&path, bytes).unwrap();
+        match File::with_libver_bounds(&path, LibraryVersion::Earliest, LibraryVersion::latest()) {
+            Err(Error::Superblock { version, .. }) => {
+                assert_eq!(version, max_superblock_version() + 1)
+            }
+            Err(e) => panic!("expected a superblock error, found {}", e),
+            Ok(_) => panic!("expected a superblock error"),
+        }
+    }
+    #[test]
+    fn read_many() {
+        let file = "examples/arrays.mat";
+        let mat_file = File::new(file).unwrap();
+        let vars = mat_file.try_read_many::<f64>(&["q", "missing"]);
+        assert!(vars[0].1.is_ok());
+        assert!(matches!(vars[1].1, Err(Error::Dataset(_))));
+    }
+    #[test]
+    fn linear_order() {
+        let file = "examples/arrays.mat";
+        let mat_file = File::new(file).unwrap();
+        let var = mat_file.array::<f64>("q").unwrap();
+        assert_eq!(var.matlab_linear_order(), vec![1., 4., 2., 5., 3., 6.]);
+    }
+    #[test]
+    fn nonzero_elements() {
+        let var = MatVar {
+            name: "a".to_string(),
+            shape: vec![2, 2],
+            data: vec![0f64, 3., 0., -1.],
+        };
+        assert_eq!(
+            var.nonzero_map().into_iter().collect::<Vec<_>>(),
+            vec![(1, 3.), (3, -1.)]
+        );
+    }
+    #[test]
+    fn variables_manifest() {
+        let file = "examples/arrays.mat";
+        let mat_file = File::new(file).unwrap();
+        let manifest = mat_file.manifest().unwrap();
+        let q = manifest.iter().find(|info| info.name == "q").unwrap();
+        assert_eq!(q.path, "/q");
+        assert_eq!(q.class, "double");
+        assert_eq!(q.shape, vec![2, 3]);
+        assert_eq!(q.size_bytes, 48);
+    }
+    #[test]
+    fn permute() {
+        let file = "examples/arrays.mat";
+        let mat_file = File::new(file).unwrap();
+        let var = mat_file.array::<f64>("q").unwrap();
+        let var = var.with_permutation(&[1, 0]).unwrap();
+        assert_eq!(var.matlab_shape(), vec![3, 2]);
+        assert_eq!(var.data, vec![1., 2., 3., 4., 5., 6.]);
+        let var = MatVar {
+            name: "a".to_string(),
+            shape: vec![2, 1, 3],
+            data: (0..6).collect(),
+        };
+        let var = var.with_permutation(&[2, 0, 1]).unwrap();
+        assert_eq!(var.matlab_shape(), vec![2, 3, 1]);
+        assert_eq!(var.data, vec![0, 3, 1, 4, 2, 5]);
+        assert!(matches!(
+            var.with_permutation(&[0, 0, 1]),
+            Err(Error::Shape { found, .. }) if found == vec![2, 3, 1]
+        ));
+    }
+    #[test]
+    fn verify_file() {
+        let file = "examples/arrays.mat";
+        let mat_file = File::new(file).unwrap();
+        assert!(mat_file.verify().is_ok());
+    }
+    #[cfg(feature = "arrow")]
+    #[test]
+    fn arrow_record_batch() {
+        use arrow::array::Float64Array;
+        use std::convert::TryFrom;
+        let file = "examples/arrays.mat";
+        let mat_file = File::new(file).unwrap();
+        let batch = mat_file
+            .array::<f64>("q")
+            .unwrap()
+            .into_record_batch::<Float64Array>()
+            .unwrap();
+        let schema = batch.schema();
+        let names: Vec<&str> = schema.fields().iter().map(|f| f.name().as_str()).collect();
+        assert_eq!(names, vec!["1", "2", "3"]);
+        assert!(schema
+            .fields()
+            .iter()
+            .all(|f| f.data_type() == &arrow::datatypes::DataType::Float64));
+        assert_eq!(batch.num_rows(), 2);
+        for (j, column) in [[1., 4.], [2., 5.], [3., 6.]].iter().enumerate() {
+            assert_eq!(
+                batch.column(j).as_any().downcast_ref::<Float64Array>(),
+                Some(&Float64Array::from(column.to_vec()))
+            );
+        }
+        let vector = mat_file
+            .array::<f64>("q")
+            .unwrap()
+            .reshape(vec![6, 1])
+            .unwrap();
+        assert_eq!(
+            Float64Array::try_from(vector).unwrap(),
+            Float64Array::from(vec![1., 4., 2., 5., 3., 6.])
+        );
+    }
+    #[test]
+    fn narrow_array() {
+        let file = "examples/arrays.mat";
+        let mat_file = File::new(file).unwrap();
+        let var = mat_file.array_narrow::<i64, u8>("q").unwrap();
+        assert_eq!(var.data, vec![1, 4, 2, 5, 3, 6]);
+        assert!(matches!(
+            mat_file.array_narrow::<i64, u8>("w"),
+            Err(Error::Overflow { index: 1, .. })
+        ));
+    }
+    #[cfg(feature = "ndarray")]
+    #[test]
+    fn ndarray_view() {
+        let file = "examples/arrays.mat";
+        let mat_file = File::new(file).unwrap();
+        let mut out = ndarray::Array2::from_elem((2, 3), 0f64);
+        mat_file.read_into_ndarray("q", out.view_mut()).unwrap();
+        assert_eq!(out[[1, 0]], 4.);
+        assert_eq!(out[[0, 2]], 3.);
+        let mut out = ndarray::Array2::<f64>::zeros((3, 2)).reversed_axes();
+        mat_file.read_into_ndarray("q", out.view_mut()).unwrap();
+        assert_eq!(out[[1, 0]], 4.);
+        assert_eq!(out[[0, 2]], 3.);
+        let mut out = ndarray::Array2::from_elem((3, 2), 0f64);
+        assert!(matches!(
+            mat_file.read_into_ndarray("q", out.view_mut()),
+            Err(Error::Shape { .. })
+        ));
+    }
+    #[test]
+    fn hard_link_target() {
+        let file = "examples/arrays.mat";
+        let mat_file = File::new(file).unwrap();
+        assert_eq!(mat_file.link_target("q").unwrap(), None);
+        assert!(mat_file.link_target("missing").is_err());
+    }
+    #[test]
+    fn soft_link_target() {
+        let (path, h5) = fixture("soft_link_target");
+        write_array::<f64>(&h5, "x", "double", &[1, 2], &[1., 2.]);
+        h5.link_soft("/x", "alias").unwrap();
+        let mat_file = File::new(&path).unwrap();
+        assert_eq!(
+            mat_file.link_target("alias").unwrap().as_deref(),
+            Some("/x")
+        );
+        assert_eq!(mat_file.link_target("x").unwrap(), None);
+        assert_eq!(mat_file.array::<f64>("alias").unwrap().data, vec![1., 2.]);
+    }
+    #[test]
+    fn csv() {
+        let file = "examples/arrays.mat";
+        let mat_file = File::new(file).unwrap();
+        let mut out = Vec::new();
+        mat_file.export_csv("q", &mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "1,2,3\n4,5,6\n");
+    }
+    #[test]
+    fn transposed_view() {
+        let file = "examples/arrays.mat";
+        let mat_file = File::new(file).unwrap();
+        let q = mat_file.array::<f64>("q").unwrap();
+        let t = q.t().unwrap();
+        assert_eq!(t.dims(), (3, 2));
+        assert_eq!(t[(2, 0)], 3.);
+        assert_eq!(t.get(0, 2), None);
+        assert_eq!(t.row(1).copied().collect::<Vec<f64>>(), vec![2., 5.]);
+        assert_eq!(t.column(1).copied().collect::<Vec<f64>>(), vec![4., 5., 6.]);
+    }
+    #[test]
+    fn complex_scalar_value() {
+        let (path, h5) = fixture("complex_scalar_value");
+        write_array(
+            &h5,
+            "z",
+            "double",
+            &[1, 1],
+            &[Complex {
+                real: 1.5,
+                imag: -2.,
+            }],
+        );
+        write_array(
+            &h5,
+            "v",
+            "double",
+            &[1, 2],
+            &[Complex { real: 1., imag: 0. }; 2],
+        );
+        let mat_file = File::new(&path).unwrap();
+        assert_eq!(mat_file.complex_scalar("z").unwrap(), (1.5, -2.));
+        assert!(matches!(
+            mat_file.complex_scalar("v"),
+            Err(Error::Shape { found, .. }) if found == vec![1, 2]
+        ));
+    }
+    #[test]
+    fn compound_records() {
+        /// Compound type of `f64` members
+        fn f64_members(names: &[&str]) -> hdf5::types::TypeDescriptor {
+            hdf5::types::TypeDescriptor::Compound(hdf5::types::CompoundType {
+                fields: names
+                    .iter()
+                    .enumerate()
+                    .map(|(index, name)| hdf5::types::CompoundField {
+                        name: name.to_string(),
+                        ty: <f64 as hdf5::H5Type>::type_descriptor(),
+                        offset: 8 * index,
+                        index,
+                    })
+                    .collect(),
+                size: 8 * names.len(),
+            })
+        }
+        #[repr(C)]
+        struct Sample {
+            time: f64,
+            value: f64,
+        }
+        unsafe impl hdf5::H5Type for Sample {
+            fn type_descriptor() -> hdf5::types::TypeDescriptor {
+                f64_members(&["time", "value"])
+            }
+        }
+        #[derive(Debug, PartialEq)]
+        #[repr(C)]
+        struct Value {
+            value: f64,
+        }
+        unsafe impl hdf5::H5Type for Value {
+            fn type_descriptor() -> hdf5::types::TypeDescriptor {
+                f64_members(&["value"])
+            }
+        }
+        let (path, h5) = fixture("compound_records");
+        let samples = [
+            Sample {
+                time: 0.,
+                value: 5.,
+            },
+            Sample {
+                time: 1.,
+                value: 6.,
+            },
+        ];
+        write_array(&h5, "r", "double", &[1, 2], &samples);
+        let mat_file = File::new(&path).unwrap();
+        let var = mat_file.compound::<Value>("r").unwrap();
+        assert_eq!(var.data, vec![Value { value: 5. }, Value { value: 6. }]);
+        assert!(matches!(
+            File::new("examples/arrays.mat")
+                .unwrap()
+                .compound::<Value>("q"),
+            Err(Error::Type { .. })
+        ));
+    }
+    #[cfg(feature = "half")]
+    #[test]
+    fn half_array() {
+        let (path, h5) = fixture("half_array");
+        let data: Vec<half::f16> = [0.5f32, -2., 1024.]
+            .iter()
+            .map(|&x| half::f16::from_f32(x))
+            .collect();
+        write_array(&h5, "h", "single", &[1, 3], &data);
+        let mat_file = File::new(&path).unwrap();
+        let var = mat_file.array_f16("h").unwrap();
+        assert_eq!(var.matlab_shape(), vec![1, 3]);
+        assert_eq!(
+            var.data.iter().map(|x| x.to_f32()).collect::<Vec<f32>>(),
+            vec![0.5, -2., 1024.]
+        );
+        assert!(matches!(
+            File::new("examples/arrays.mat").unwrap().array_f16("q"),
+            Err(Error::Type { .. })
+        ));
+    }
+    #[test]
+    fn chunked_array() {
+        let (path, h5) = fixture("chunked_array");
+        // a 5x3 Matlab array in 2x2 chunks, the last chunks of both dimensions being partial
+        let data: Vec<f64> = (0..15).map(f64::from).collect();
+        let dataset = h5
+            .new_dataset::<f64>()
+            .shape(vec![3, 5])
+            .chunk(vec![2, 2])
+            .deflate(4)
+            .create("c")
+            .unwrap();
+        dataset.write_raw(&data).unwrap();
+        write_class(&dataset, "double");
+        let mat_file = File::new(&path).unwrap();
+        let var = mat_file.array_chunked::<f64>("c").unwrap();
+        assert_eq!(var.matlab_shape(), vec![5, 3]);
+        assert_eq!(var.data, data);
+        let arrays = File::new("examples/arrays.mat").unwrap();
+        assert_eq!(
+            arrays.array_chunked::<f64>("q").unwrap().data,
+            vec![1., 4., 2., 5., 3., 6.]
+        );
+    }
+    #[test]
+    fn real_complex_scalar() {
+        let file = "examples/arrays.mat";
+        let mat_file = File::new(file).unwrap();
+        assert!(matches!(
+            mat_file.complex_scalar("q"),
+            Err(Error::Type { .. })
+        ));
+    }
+    #[test]
+    fn nan_as_none() {
+        let var = MatVar {
+            name: "x".to_string(),
+            shape: vec![3, 1],
+            data: vec![1., f64::NAN, f64::INFINITY],
+        };
+        assert_eq!(
+            var.into_option_vec(),
+            vec![Some(1.), None, Some(f64::INFINITY)]
+        );
+        let file = "examples/arrays.mat";
+        let mat_file = File::new(file).unwrap();
+        assert!(mat_file
+            .array_optional_f64("q")
+            .unwrap()
+            .iter()
+            .all(Option::is_some));
+    }
+    #[test]
+    fn char_matrix_text() {
+        let (path, h5) = fixture("char_matrix_text");
+        // ['ab ';'cde'] in column-major order
+        let text: Vec<u16> = "acbd e".encode_utf16().collect();
+        write_array(&h5, "t", "char", &[2, 3], &text);
+        let mat_file = File::new(&path).unwrap();
+        assert_eq!(mat_file.text("t").unwrap(), "ab\ncde");
+        assert!(matches!(mat_file.text("missing"), Err(Error::Dataset(_))));
+    }
+    #[test]
+    fn char_pages() {
+        let (path, h5) = fixture("char_pages");
+        // A(:, :, 1) = ['ab';'cd'], A(:, :, 2) = ['ef';'g '] in column-major order
+        let text: Vec<u16> = "acbdegf ".encode_utf16().collect();
+        write_array(&h5, "p", "char", &[2, 2, 2], &text);
+        let mat_file = File::new(&path).unwrap();
+        assert_eq!(
+            mat_file.string_pages("p").unwrap(),
+            vec![vec!["ab", "cd"], vec!["ef", "g"]]
+        );
+    }
+    #[test]
+    fn string_pages_class() {
+        let file = "examples/arrays.mat";
+        let mat_file = File::new(file).unwrap();
+        assert!(matches!(
+            mat_file.string_pages("q"),
+            Err(Error::Class { .. })
+        ));
+    }
+    #[cfg(feature = "nalgebra")]
+    #[test]
+    fn into_matrix_3d() {
+        let var = MatVar {
+            name: "x".to_string(),
+            shape: vec![10, 3, 2],
+            data: vec![0f64; 60],
+        };
+        match var.into_matrix() {
+            Err(e @ Error::Shape { .. }) => {
+                assert!(e.to_string().contains("[2, 3, 10]"));
+                assert!(e.to_string().contains("into_slices"));
+            }
+            _ => panic!("expected a shape error"),
+        }
+    }
+    #[test]
+    fn concat() {
+        let file = "examples/arrays.mat";
+        let mat_file = File::new(file).unwrap();
+        let q = || mat_file.array::<f64>("q").unwrap();
+        let rows = q().concat(q(), 0).unwrap();
+        assert_eq!(rows.matlab_shape(), vec![4, 3]);
+        assert_eq!(rows.data[..4], [1., 4., 1., 4.]);
+        let columns = q().concat(q(), 1).unwrap();
+        assert_eq!(columns.matlab_shape(), vec![2, 6]);
+        assert_eq!(columns.data[4..8], [3., 6., 1., 4.]);
+        let pages = q().concat(q(), 2).unwrap();
+        assert_eq!(pages.matlab_shape(), vec![2, 3, 2]);
+        assert!(matches!(
+            q().concat(mat_file.array("w").unwrap(), 0),
+            Err(Error::Shape { .. })
+        ));
+    }
+    #[test]
+    fn compression() {
+        let file = "examples/arrays.mat";
+        let mat_file = File::new(file).unwrap();
+        let ratio = mat_file.compression_ratio("q").unwrap();
+        assert!(ratio.is_finite() && ratio > 0.);
+    }
+    #[test]
+    fn owned_iter() {
+        let file = "examples/arrays.mat";
+        let names = File::new(file).unwrap().variables().unwrap();
+        let values: Vec<(String, MatValue)> = File::new(file)
+            .unwrap()
+            .into_iter()
+            .collect::<Result<_>>()
+            .unwrap();
+        assert_eq!(
+            values.into_iter().map(|(name, _)| name).collect::<Vec<_>>(),
+            names
+        );
+    }
+    #[cfg(feature = "nalgebra")]
+    #[test]
+    fn nan_policy() {
+        let var = || MatVar {
+            name: "x".to_string(),
+            shape: vec![2, 2],
+            data: vec![1., f64::NAN, 3., f64::NEG_INFINITY],
+        };
+        assert!(var().into_matrix_checked(NanPolicy::default()).is_ok());
+        assert!(matches!(
+            var().into_matrix_checked(NanPolicy::Error),
+            Err(Error::NonFinite { indices, .. }) if indices == vec![1, 3]
+        ));
+        let matrix = var().into_matrix_checked(NanPolicy::Replace(0.)).unwrap();
+        assert_eq!(matrix[(1, 0)], 0.);
+    }
+    #[test]
+    fn missing_ref_array() {
+        let file = "examples/arrays.mat";
+        let mat_file = File::new(file).unwrap();
+        assert!(matches!(
+            mat_file.ref_array::<f64>("a"),
+            Err(Error::Dataset(name)) if name == "#refs#/a"
+        ));
+    }
+    #[cfg(feature = "nalgebra")]
+    #[test]
+    fn all_matrices() {
+        let file = "examples/arrays.mat";
+        let mat_file = File::new(file).unwrap();
+        let matrices = mat_file.all_matrices().unwrap();
+        let (_, q) = matrices.iter().find(|(name, _)| name == "q").unwrap();
+        assert_eq!(q.shape(), (2, 3));
+        assert_eq!(q[(1, 0)], 4.);
+    }
+    #[test]
+    fn tagged() {
+        let file = "examples/arrays.mat";
+        let mat_file = File::new(file).unwrap();
+        match mat_file.read_tagged("q").unwrap() {
+            (MatType::Double, NumericArray::F64(var)) => assert_eq!(var.n_row(), 2),
+            _ => panic!("expected a double array"),
+        }
+        assert_eq!(MatType::from_class("uint16"), Some(MatType::Uint16));
+        assert_eq!(MatType::from_class("struct"), None);
+    }
+    #[test]
+    fn blas_layout() {
+        let file = "examples/arrays.mat";
+        let mat_file = File::new(file).unwrap();
+        let (data, lda, n_column) = mat_file.array::<f64>("q").unwrap().as_blas().unwrap();
+        assert_eq!((lda, n_column), (2, 3));
+        assert_eq!(data[1 + 2 * lda], 6.);
+    }
+    #[cfg(feature = "image")]
+    #[test]
+    fn indexed_rgb_image() {
+        let (path, h5) = fixture("indexed_rgb_image");
+        // X = uint8([0 1; 0 0]) with the colormap [1 0 0; 0 0 1]
+        write_array::<u8>(&h5, "X", "uint8", &[2, 2], &[0, 0, 1, 0]);
+        write_array::<f64>(&h5, "map", "double", &[2, 3], &[1., 0., 0., 0., 0., 1.]);
+        let mat_file = File::new(&path).unwrap();
+        let image = mat_file.indexed_image("X", "map").unwrap();
+        assert_eq!((image.width(), image.height()), (2, 2));
+        assert_eq!(image.get_pixel(0, 0).0, [255, 0, 0]);
+        assert_eq!(image.get_pixel(1, 0).0, [0, 0, 255]);
+        assert_eq!(image.get_pixel(0, 1).0, [255, 0, 0]);
+        assert_eq!(image.get_pixel(1, 1).0, [255, 0, 0]);
+    }
+    #[cfg(feature = "image")]
+    #[test]
+    fn colormap_index() {
+        let file = "examples/arrays.mat";
+        let mat_file = File::new(file).unwrap();
+        assert!(matches!(
+            mat_file.indexed_image("q", "q"),
+            Err(Error::ColormapIndex {
+                index: 4,
+                n_color: 2,
+                ..
+            })
+        ));
+    }
+    #[test]
+    fn zero_stride() {
+        let file = "examples/arrays.mat";
+        let mat_file = File::new(file).unwrap();
+        assert!(matches!(
+            mat_file.array_strided::<f64>("q", 0),
+            Err(Error::Stride(_))
+        ));
+        assert!(matches!(
+            mat_file.array_strided::<f64>("q", 2),
+            Err(Error::Shape { .. })
+        ));
+    }
+    #[test]
+    fn strided_vector() {
+        let (path, h5) = fixture("strided_vector");
+        let row: Vec<f64> = (0..10).map(f64::from).collect();
+        write_array(&h5, "row", "double", &[1, 10], &row);
+        let column: Vec<f64> = (0..6).map(f64::from).collect();
+        write_array(&h5, "column", "double", &[6, 1], &column);
+        let mat_file = File::new(&path).unwrap();
+        let var = mat_file.array_strided::<f64>("row", 3).unwrap();
+        assert_eq!(var.data, vec![0., 3., 6., 9.]);
+        assert_eq!(var.matlab_shape(), vec![1, 4]);
+        let var = mat_file.array_strided::<f64>("column", 2).unwrap();
+        assert_eq!(var.data, vec![0., 2., 4.]);
+        assert_eq!(var.matlab_shape(), vec![3, 1]);
+    }
+    #[test]
+    fn map_object() {
+        let (path, h5) = fixture("map_object");
+        write_object(&h5, "m", "containers.Map");
+        let mat_file = File::new(&path).unwrap();
+        assert!(matches!(
+            mat_file.array::<f64>("m"),
+            Err(Error::Object { class, decode: 3, .. }) if class == "containers.Map"
+        ));
+    }
+    #[test]
+    fn empty_struct_and_cell() {
+        let (path, h5) = fixture("empty_struct_and_cell");
+        write_empty(&h5, "s", "struct", &[0, 0]);
+        write_empty(&h5, "c", "cell", &[0, 0]);
+        let mat_file = File::new(&path).unwrap();
+        for name in ["s", "c"] {
+            let empty = mat_file.structure(name).unwrap();
+            assert!(empty.is_empty());
+            assert!(empty.field_names.is_empty());
+        }
+    }
+    #[test]
+    fn read_empty() {
+        let (path, h5) = fixture("read_empty");
+        write_empty(&h5, "x", "double", &[0, 0]);
+        write_empty(&h5, "y", "double", &[0, 3]);
+        write_empty(&h5, "c", "char", &[0, 0]);
+        let mat_file = File::new(&path).unwrap();
+        match mat_file.read("x").unwrap() {
+            MatValue::Numeric(x) => {
+                assert!(x.data.is_empty());
+                assert_eq!(x.matlab_shape(), vec![0, 0]);
+            }
+            _ => panic!("expected a numeric array"),
+        }
+        match mat_file.read("y").unwrap() {
+            MatValue::Numeric(y) => assert_eq!(y.matlab_shape(), vec![0, 3]),
+            _ => panic!("expected a numeric array"),
+        }
+        assert!(matches!(mat_file.read("c"), Ok(MatValue::Char(c)) if c.is_empty()));
+    }
+    #[test]
+    fn one_dimension_dataset() {
+        let (path, h5) = fixture("one_dimension_dataset");
+        let dataset = h5.new_dataset::<f64>().shape(3).create("v").unwrap();
+        dataset.write_raw(&[1., 2., 3.]).unwrap();
+        write_class(&dataset, "double");
+        let mat_file = File::new(&path).unwrap();
+        let var = mat_file.array::<f64>("v").unwrap();
+        assert_eq!(var.shape(), &[3]);
+        assert_eq!((var.n_row(), var.n_column()), (1, 3));
+        assert_eq!(var.matlab_shape(), vec![1, 3]);
+        assert_eq!(var.raw(), vec![1., 2., 3.]);
+    }
+    #[test]
+    fn logical_fixture() {
+        let (path, h5) = fixture("logical_fixture");
+        let dataset = write_array::<u8>(&h5, "b", "logical", &[2, 3], &[1, 0, 0, 1, 1, 0]);
+        write_attr(&dataset, "MATLAB_int_decode", 1i32);
+        let mat_file = File::new(&path).unwrap();
+        let b = mat_file.logical_array("b").unwrap();
+        assert_eq!(b.matlab_shape(), vec![2, 3]);
+        assert_eq!(b.raw(), vec![true, false, false, true, true, false]);
+        assert!(matches!(
+            mat_file.logical_array("missing"),
+            Err(Error::Dataset(_))
+        ));
+    }
+    #[test]
+    fn duration_object() {
+        let (path, h5) = fixture("duration_object");
+        write_object(&h5, "d", "duration");
+        write_array::<f64>(&h5, "x", "double", &[1, 1], &[1.]);
+        let mat_file = File::new(&path).unwrap();
+        assert!(matches!(
+            mat_file.array::<f64>("d"),
+            Err(Error::Object { class, decode: 3, .. }) if class == "duration"
+        ));
+        assert_eq!(mat_file.array::<f64>("x").unwrap().data, vec![1.]);
+    }
+    #[test]
+    fn checked_strides() {
+        assert_eq!(column_major_strides(&[2, 3, 4]).unwrap(), vec![1, 2, 6]);
+        assert!(matches!(
+            column_major_strides(&[usize::MAX, 2, 1]),
+            Err(Error::Overflow { index: 1, .. })
+        ));
+        assert!(matches!(
+            checked_product(&[usize::MAX, std::mem::size_of::<f64>()]),
+            Err(Error::Overflow { index: 1, .. })
+        ));
+    }
+    #[test]
+    fn unsupported_class() {
+        let (path, h5) = fixture("unsupported_class");
+        let group = h5.create_group("f").unwrap();
+        write_class(&group, "function_handle");
+        write_array::<u8>(&h5, "t", "table", &[1, 2], &[0, 0]);
+        let mat_file = File::new(&path).unwrap();
+        match mat_file.read("f").unwrap() {
+            MatValue::Unsupported { class, shape } => {
+                assert_eq!(class, "function_handle");
+                assert!(shape.is_empty());
+            }
+            value => panic!("expected an unsupported value, found {:?}", value),
+        }
+        match mat_file.read("t").unwrap() {
+            MatValue::Unsupported { class, shape } => {
+                assert_eq!(class, "table");
+                assert_eq!(shape, vec![2, 1]);
+            }
+            value => panic!("expected an unsupported value, found {:?}", value),
+        }
+    }
+    #[test]
+    fn categorical_object() {
+        let (path, h5) = fixture("categorical_object");
+        write_object(&h5, "c", "categorical");
+        write_array::<u32>(&h5, "x", "uint32", &[1, 2], &[1, 2]);
+        let mat_file = File::new(&path).unwrap();
+        assert!(matches!(
+            mat_file.array::<u32>("c"),
+            Err(Error::Object { class, decode: 3, .. }) if class == "categorical"
+        ));
+        assert_eq!(mat_file.array::<u32>("x").unwrap().data, vec![1, 2]);
+    }
+    #[test]
+    fn timetable_object() {
+        let (path, h5) = fixture("timetable_object");
+        write_object(&h5, "tt", "timetable");
+        let mat_file = File::new(&path).unwrap();
+        match mat_file.array::<f64>("tt") {
+            Err(Error::Object {
+                name,
+                class,
+                decode,
+            }) => {
+                assert_eq!(name, "tt");
+                assert_eq!(class, "timetable");
+                assert_eq!(decode, 3);
+            }
+            value => panic!("expected an object error, found {:?}", value),
+        }
+    }
+    #[test]
+    fn function_handle_fixture() {
+        let (path, h5) = fixture("function_handle");
+        // f = @sin
+        let group = h5.create_group("f").unwrap();
+        write_class(&group, "function_handle");
+        let nested = group.create_group("function_handle").unwrap();
+        write_class(&nested, "struct");
+        let text: Vec<u16> = "sin".encode_utf16().collect();
+        write_array(&nested, "function", "char", &[1, 3], &text);
+        write_array::<f64>(&nested, "type", "double", &[1, 1], &[0.]);
+        // g with a member of the wrong class
+        let group = h5.create_group("g").unwrap();
+        write_class(&group, "function_handle");
+        write_array::<f64>(&group, "function", "double", &[1, 1], &[0.]);
+        let mat_file = File::new(&path).unwrap();
+        assert_eq!(mat_file.function_handle("f").unwrap(), "sin");
+        assert!(matches!(
+            mat_file.function_handle("g"),
+            Err(Error::Class { .. })
+        ));
+        assert!(matches!(
+            mat_file.function_handle("f/function_handle"),
+            Err(Error::Class { .. })
+        ));
+    }
+    #[test]
+    fn empty_fields() {
+        let (path, h5) = fixture("empty_fields");
+        let group = write_struct(&h5, "s", &["x", "c", "y"]);
+        write_empty(&group, "x", "double", &[0, 0]);
+        write_empty(&group, "c", "char", &[0, 0]);
+        write_array::<f64>(&group, "y", "double", &[1, 1], &[2.]);
+        let mat_file = File::new(&path).unwrap();
+        let s = mat_file.structure("s").unwrap();
+        let values = s.flatten().unwrap();
+        match &values["x"] {
+            MatValue::Numeric(x) => {
+                assert!(x.data.is_empty());
+                assert_eq!(x.matlab_shape(), vec![0, 0]);
+            }
+            value => panic!("expected a numeric array, found {:?}", value),
+        }
+        assert!(matches!(&values["c"], MatValue::Char(c) if c.is_empty()));
+        assert!(matches!(&values["y"], MatValue::Numeric(y) if y.data == [2.]));
+        assert!(s.read_all_fields().iter().all(|(_, value)| value.is_ok()));
+        #[cfg(feature = "json")]
+        assert_eq!(
+            s.to_json().unwrap(),
+            serde_json::json!({"x": [], "c": "", "y": 2.0})
+        );
+    }
+    #[test]
+    fn validate_unreadable() {
+        let (path, h5) = fixture("validate_unreadable");
+        h5.new_dataset::<f64>()
+            .shape(vec![3, 1])
+            .create("noclass")
+            .unwrap();
+        h5.create_group("nogroupclass").unwrap();
+        write_array::<f64>(&h5, "x", "double", &[1, 3], &[1., 2., 3.]);
+        let mat_file = File::new(&path).unwrap();
+        let schema = [
+            VarSpec::new("noclass", "double"),
+            VarSpec::new("nogroupclass", "struct"),
+            VarSpec::new("x", "single").shape(&[1, 3]),
+        ];
+        let errors = mat_file.validate(&schema).unwrap();
+        assert_eq!(errors.len(), 3);
+        assert!(matches!(&errors[0], SchemaError::Unreadable { name, .. } if name == "noclass"));
+        assert!(
+            matches!(&errors[1], SchemaError::Unreadable { name, .. } if name == "nogroupclass")
+        );
+        assert_eq!(
+            errors[2],
+            SchemaError::Class {
+                name: "x".to_string(),
+                expected: "single".to_string(),
+                found: "double".to_string(),
+            }
+        );
+    }
+    #[test]
+    fn stream_bytes() {
+        use std::io::Read;
+        let (path, h5) = fixture("stream_bytes");
+        let n = 2 * BLOCK_BYTES + 123;
+        let bytes: Vec<u8> = (0..n).map(|k| (k % 251) as u8).collect();
+        write_array(&h5, "row", "uint8", &[1, n], &bytes);
+        let signed: Vec<i8> = (0..1000).map(|k| (k % 256) as u8 as i8).collect();
+        write_array(&h5, "column", "int8", &[1000, 1], &signed);
+        write_array::<f64>(&h5, "x", "double", &[1, 1], &[1.]);
+        write_array::<u8>(&h5, "m", "uint8", &[2, 2], &[0, 1, 2, 3]);
+        let mat_file = File::new(&path).unwrap();
+        let mut streamed = Vec::new();
+        mat_file
+            .reader("row")
+            .unwrap()
+            .read_to_end(&mut streamed)
+            .unwrap();
+        assert_eq!(streamed, mat_file.array::<u8>("row").unwrap().data);
+        let mut reader = mat_file.reader("column").unwrap();
+        let mut streamed = Vec::new();
+        let mut buffer = [0u8; 7];
+        loop {
+            match reader.read(&mut buffer).unwrap() {
+                0 => break,
+                k => streamed.extend_from_slice(&buffer[..k]),
+            }
+        }
+        assert_eq!(
+            streamed,
+            signed.iter().map(|&x| x as u8).collect::<Vec<u8>>()
+        );
+        assert!(matches!(mat_file.reader("x"), Err(Error::Class { .. })));
+        assert!(matches!(mat_file.reader("m"), Err(Error::Shape { .. })));
+    }
+    #[test]
+    fn field_declaration_order() {
+        let (path, h5) = fixture("field_declaration_order");
+        let group = write_struct(&h5, "s", &["zeta", "alpha", "mid"]);
+        for (name, value) in [("alpha", 1.), ("mid", 2.), ("zeta", 3.)] {
+            write_array::<f64>(&group, name, "double", &[1, 1], &[value]);
+        }
+        let mat_file = File::new(&path).unwrap();
+        let s = mat_file.structure("s").unwrap();
+        assert_eq!(s.field_names, vec!["zeta", "alpha", "mid"]);
+        assert_eq!(s.len(), 1);
+    }
+    #[test]
+    fn field_name_strings() {
+        use hdf5::types::{FixedUnicode, VarLenUnicode};
+        /// Writes a struct with the fields `a` and `bc`, its `MATLAB_fields` attribute holding `names`
+        fn write_named_struct<T: hdf5::H5Type>(h5: &hdf5::File, name: &str, names: &[T]) {
+            let group = h5.create_group(name).unwrap();
+            write_class(&group, "struct");
+            group
+                .new_attr::<T>()
+                .shape(names.len())
+                .create("MATLAB_fields")
+                .unwrap()
+                .write_raw(names)
+                .unwrap();
+            write_array::<f64>(&group, "a", "double", &[1, 1], &[1.]);
+            write_array::<f64>(&group, "bc", "double", &[1, 1], &[2.]);
+        }
+        let (path, h5) = fixture("field_name_strings");
+        let ascii = [
+            FixedAscii::<4>::from_ascii("a").unwrap(),
+            FixedAscii::<4>::from_ascii("bc").unwrap(),
+        ];
+        write_named_struct(&h5, "ascii", &ascii);
+        let unicode: [FixedUnicode<4>; 2] = ["a".parse().unwrap(), "bc".parse().unwrap()];
+        write_named_struct(&h5, "unicode", &unicode);
+        let var_len: [VarLenUnicode; 2] = ["a".parse().unwrap(), "bc".parse().unwrap()];
+        write_named_struct(&h5, "var_len", &var_len);
+        let mat_file = File::new(&path).unwrap();
+        for name in ["ascii", "unicode", "var_len"] {
+            let s = mat_file.structure(name).unwrap();
+            assert_eq!(s.field_names, vec!["a", "bc"], "{}", name);
+            assert_eq!(s.scalar_field::<f64>("bc").unwrap(), 2.);
+        }
+    }
+    #[test]
+    fn logical_flag() {
+        let (path, h5) = fixture("logical_flag");
+        write_array(&h5, "on", "logical", &[1, 1], &[1u8]);
+        write_array(&h5, "off", "logical", &[1, 1], &[0u8]);
+        write_array(&h5, "mask", "logical", &[1, 2], &[1u8, 0]);
+        let mat_file = File::new(&path).unwrap();
+        assert!(mat_file.flag("on").unwrap());
+        assert!(!mat_file.flag("off").unwrap());
+        assert!(matches!(mat_file.flag("missing"), Err(Error::Dataset(_))));
+        assert!(matches!(mat_file.flag("mask"), Err(Error::Shape { .. })));
+    }
+    #[test]
+    fn class_handler() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+        let (path, h5) = fixture("class_handler");
+        let group = h5.create_group("g").unwrap();
+        write_class(&group, "custom");
+        write_array::<f64>(&h5, "d", "custom", &[1, 1], &[1.]);
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&calls);
+        let mat_file = File::new(&path).unwrap().with_handler(
+            "custom",
+            Box::new(move |group| {
+                counter.fetch_add(1, Ordering::SeqCst);
+                Ok(MatValue::Char(group.name()))
+            }),
+        );
+        assert!(matches!(mat_file.read("g").unwrap(), MatValue::Char(name) if name == "/g"));
+        assert!(matches!(
+            mat_file.read("d").unwrap(),
+            MatValue::Unsupported { class, .. } if class == "custom"
+        ));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+    #[test]
+    fn read_struct_fields() {
+        let (path, h5) = fixture("read_struct_fields");
+        let group = write_struct(&h5, "s", &["a", "big", "b"]);
+        write_array::<f64>(&group, "a", "double", &[1, 2], &[1., 2.]);
+        write_array::<f64>(&group, "big", "double", &[1, 4], &[0.; 4]);
+        write_array::<f64>(&group, "b", "double", &[1, 1], &[3.]);
+        let mat_file = File::new(&path).unwrap();
+        let s = mat_file.structure("s").unwrap();
+        let fields = s.read_fields::<f64>(&["b", "a"]).unwrap();
+        assert_eq!(fields.len(), 2);
+        assert_eq!(fields[0].0, "b");
+        assert_eq!(fields[0].1.data, vec![3.]);
+        assert_eq!(fields[1].0, "a");
+        assert_eq!(fields[1].1.matlab_shape(), vec![1, 2]);
+        assert!(matches!(
+            s.read_fields::<f64>(&["a", "none"]),
+            Err(Error::Field(name)) if name == "none"
+        ));
+    }
+    #[test]
+    fn scalar_fields_and_substruct() {
+        let (path, h5) = fixture("scalar_fields_and_substruct");
+        // cfg.tolerance = 1e-6; cfg.size = [1 2]; cfg.solver.iter = int32(10)
+        let group = write_struct(&h5, "cfg", &["tolerance", "size", "solver"]);
+        write_array::<f64>(&group, "tolerance", "double", &[1, 1], &[1e-6]);
+        write_array::<f64>(&group, "size", "double", &[1, 2], &[1., 2.]);
+        let solver = write_struct(&group, "solver", &["iter"]);
+        write_array::<i32>(&solver, "iter", "int32", &[1, 1], &[10]);
+        let mat_file = File::new(&path).unwrap();
+        let cfg = mat_file.structure("cfg").unwrap();
+        assert_eq!(cfg.scalar_field::<f64>("tolerance").unwrap(), 1e-6);
+        assert!(matches!(
+            cfg.scalar_field::<f64>("size"),
+            Err(Error::Shape { .. })
+        ));
+        let solver = cfg.substruct("solver").unwrap();
+        assert_eq!(solver.field_names, vec!["iter"]);
+        assert_eq!(solver.scalar_field::<i32>("iter").unwrap(), 10);
+        assert!(matches!(cfg.substruct("none"), Err(Error::Field(_))));
+    }
+    #[test]
+    fn collect_struct_array_field() {
+        let (path, h5) = fixture("collect_struct_array_field");
+        // s(1).x = 1; s(2).x = 2; s(3).x = 3
+        let refs = h5.create_group("#refs#").unwrap();
+        let references: Vec<hdf5::ObjectReference1> = ["a", "b", "c"]
+            .iter()
+            .zip([1., 2., 3.])
+            .map(|(name, value)| {
+                write_array::<f64>(&refs, name, "double", &[1, 1], &[value]);
+                h5.reference(&format!("/#refs#/{}", name)).unwrap()
+            })
+            .collect();
+        let group = write_struct(&h5, "s", &["x"]);
+        group
+            .new_dataset::<hdf5::ObjectReference1>()
+            .shape(vec![3, 1])
+            .create("x")
+            .unwrap()
+            .write_raw(&references)
+            .unwrap();
+        let group = write_struct(&h5, "t", &["x"]);
+        write_array::<f64>(&group, "x", "double", &[1, 1], &[4.]);
+        let mat_file = File::new(&path).unwrap();
+        let s = mat_file.structure("s").unwrap();
+        assert_eq!(s.len(), 3);
+        let x = s.collect_field::<f64>("x").unwrap();
+        assert_eq!(x.matlab_shape(), vec![1, 3]);
+        assert_eq!(x.data, vec![1., 2., 3.]);
+        let t = mat_file.structure("t").unwrap();
+        assert_eq!(t.len(), 1);
+        assert_eq!(t.collect_field::<f64>("x").unwrap().data, vec![4.]);
+        assert!(matches!(s.collect_field::<f64>("y"), Err(Error::Field(_))));
+    }
+    #[test]
+    fn char_cell() {
+        let (path, h5) = fixture("char_cell");
+        // c = {'ab', ''; 'cde', 'f'}
+        let refs = h5.create_group("#refs#").unwrap();
+        let references: Vec<hdf5::ObjectReference1> =
+            [("a", "ab"), ("b", "cde"), ("c", ""), ("d", "f")]
+                .iter()
+                .map(|(name, text)| {
+                    if text.is_empty() {
+                        write_empty(&refs, name, "char", &[0, 0]);
+                    } else {
+                        let chars: Vec<u16> = text.encode_utf16().collect();
+                        write_array(&refs, name, "char", &[1, chars.len()], &chars);
+                    }
+                    h5.reference(&format!("/#refs#/{}", name)).unwrap()
+                })
+                .collect();
+        write_array(&h5, "c", "cell", &[2, 2], &references);
+        let mat_file = File::new(&path).unwrap();
+        assert_eq!(
+            mat_file.string_cell("c").unwrap(),
+            vec!["ab", "cde", "", "f"]
+        );
+        assert!(matches!(
+            mat_file.string_cell("#refs#/a"),
+            Err(Error::Class { .. })
+        ));
+    }
+    #[test]
+    fn flatten_nested_struct() {
+        let (path, h5) = fixture("flatten_nested_struct");
+        // cfg.tol = 1e-6; cfg.solver.method = 'newton'; cfg.solver.limits.iter = uint32(5)
+        let cfg = write_struct(&h5, "cfg", &["tol", "solver"]);
+        write_array::<f64>(&cfg, "tol", "double", &[1, 1], &[1e-6]);
+        let solver = write_struct(&cfg, "solver", &["method", "limits"]);
+        let text: Vec<u16> = "newton".encode_utf16().collect();
+        write_array(&solver, "method", "char", &[1, 6], &text);
+        let limits = write_struct(&solver, "limits", &["iter"]);
+        write_array::<u32>(&limits, "iter", "uint32", &[1, 1], &[5]);
+        let mat_file = File::new(&path).unwrap();
+        let values = mat_file.structure("cfg").unwrap().flatten().unwrap();
+        let mut keys: Vec<&String> = values.keys().collect();
+        keys.sort();
+        assert_eq!(keys, ["solver.limits.iter", "solver.method", "tol"]);
+        assert!(matches!(&values["tol"], MatValue::Numeric(tol) if tol.data == [1e-6]));
+        assert!(matches!(&values["solver.method"], MatValue::Char(method) if method == "newton"));
+        assert!(
+            matches!(&values["solver.limits.iter"], MatValue::Numeric(iter) if iter.data == [5.])
+        );
+    }
+    #[test]
+    fn string_fields() {
+        let (path, h5) = fixture("string_fields");
+        let group = write_struct(&h5, "cfg", &["method", "padded", "tol"]);
+        let text: Vec<u16> = "newton".encode_utf16().collect();
+        write_array(&group, "method", "char", &[1, 6], &text);
+        let text: Vec<u16> = "bfgs  \0".encode_utf16().collect();
+        write_array(&group, "padded", "char", &[1, 7], &text);
+        write_array::<f64>(&group, "tol", "double", &[1, 1], &[1e-6]);
+        let mat_file = File::new(&path).unwrap();
+        let cfg = mat_file.structure("cfg").unwrap();
+        assert_eq!(cfg.string_field("method").unwrap(), "newton");
+        assert_eq!(cfg.string_field("padded").unwrap(), "bfgs");
+        assert!(matches!(
+            cfg.string_field("tol"),
+            Err(Error::Class { found, .. }) if found == "double"
+        ));
+        assert!(matches!(cfg.string_field("none"), Err(Error::Field(_))));
+    }
+    #[test]
+    fn partially_readable_struct() {
+        let (path, h5) = fixture("partially_readable_struct");
+        // the field `missing` is declared but not stored
+        let group = write_struct(&h5, "s", &["x", "f", "missing"]);
+        write_array::<f64>(&group, "x", "double", &[1, 2], &[1., 2.]);
+        let handle = group.create_group("f").unwrap();
+        write_class(&handle, "function_handle");
+        let mat_file = File::new(&path).unwrap();
+        let fields = mat_file.structure("s").unwrap().read_all_fields();
+        let names: Vec<&str> = fields.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["x", "f", "missing"]);
+        assert!(matches!(&fields[0].1, Ok(MatValue::Numeric(x)) if x.data == [1., 2.]));
+        assert!(matches!(
+            &fields[1].1,
+            Ok(MatValue::Unsupported { class, .. }) if class == "function_handle"
+        ));
+        assert!(fields[2].1.is_err());
+    }
+    #[test]
+    fn struct_columns() {
+        let (path, h5) = fixture("struct_columns");
+        let group = write_struct(&h5, "table", &["time", "value"]);
+        write_array::<f64>(&group, "time", "double", &[3, 1], &[0., 1., 2.]);
+        write_array::<f64>(&group, "value", "double", &[1, 3], &[5., 6., 7.]);
+        let group = write_struct(&h5, "ragged", &["time", "value"]);
+        write_array::<f64>(&group, "time", "double", &[3, 1], &[0., 1., 2.]);
+        write_array::<f64>(&group, "value", "double", &[2, 1], &[5., 6.]);
+        let mat_file = File::new(&path).unwrap();
+        let columns = mat_file
+            .structure("table")
+            .unwrap()
+            .into_columns::<f64>()
+            .unwrap();
+        assert_eq!(columns.len(), 2);
+        assert_eq!(columns[0].0, "time");
+        assert_eq!(columns[0].1.data, vec![0., 1., 2.]);
+        assert_eq!(columns[1].0, "value");
+        assert_eq!(columns[1].1.data, vec![5., 6., 7.]);
+        assert!(matches!(
+            mat_file.structure("ragged").unwrap().into_columns::<f64>(),
+            Err(Error::Shape { found, .. }) if found == vec![2, 1]
+        ));
+    }
+    #[test]
+    fn wrapped_arrays() {
+        let (path, h5) = fixture("wrapped_arrays");
+        let group = h5.create_group("named").unwrap();
+        write_array::<f64>(&group, "value", "double", &[1, 2], &[1., 2.]);
+        write_array::<f64>(&group, "units", "double", &[1, 1], &[0.]);
+        let group = h5.create_group("single").unwrap();
+        write_array::<f64>(&group, "data", "double", &[2, 1], &[3., 4.]);
+        let group = h5.create_group("several").unwrap();
+        write_array::<f64>(&group, "a", "double", &[1, 1], &[0.]);
+        write_array::<f64>(&group, "b", "double", &[1, 1], &[0.]);
+        let group = write_struct(&h5, "s", &["x"]);
+        write_array::<f64>(&group, "x", "double", &[1, 1], &[0.]);
+        let mat_file = File::new(&path).unwrap();
+        let named = mat_file.array::<f64>("named").unwrap();
+        assert_eq!(named.data, vec![1., 2.]);
+        assert_eq!(named.matlab_shape(), vec![1, 2]);
+        let named = mat_file
+            .read_builder("named")
+            .expect_shape(&[1, 2])
+            .finish()
+            .unwrap();
+        assert_eq!(named.data, vec![1., 2.]);
+        assert_eq!(mat_file.array::<f64>("single").unwrap().data, vec![3., 4.]);
+        assert!(matches!(
+            mat_file.array::<f64>("several"),
+            Err(Error::Dataset(_))
+        ));
+        assert!(matches!(
+            mat_file.array::<f64>("s"),
+            Err(Error::Reader { .. })
+        ));
+    }
+    #[cfg(feature = "nalgebra")]
+    #[test]
+    fn struct_matrix_field() {
+        let (path, h5) = fixture("struct_matrix_field");
+        // s.m = [1 2 3; 4 5 6]
+        let group = write_struct(&h5, "s", &["m", "cube"]);
+        write_array::<f64>(&group, "m", "double", &[2, 3], &[1., 4., 2., 5., 3., 6.]);
+        write_array::<f64>(&group, "cube", "double", &[2, 2, 2], &[0.; 8]);
+        let mat_file = File::new(&path).unwrap();
+        let s = mat_file.structure("s").unwrap();
+        assert_eq!(
+            s.matrix_field::<f64>("m").unwrap(),
+            nalgebra::DMatrix::from_row_slice(2, 3, &[1., 2., 3., 4., 5., 6.])
+        );
+        assert!(matches!(
+            s.matrix_field::<f64>("cube"),
+            Err(Error::Shape { .. })
+        ));
+        assert!(matches!(
+            s.matrix_field::<f64>("none"),
+            Err(Error::Field(_))
+        ));
+    }
+    #[test]
+    fn stored_bytes() {
+        let file = "examples/arrays.mat";
+        let mat_file = File::new(file).unwrap();
+        let values = mat_file
+            .hdf5()
+            .dataset("q")
+            .unwrap()
+            .read_raw::<f64>()
+            .unwrap();
+        assert_eq!(
+            mat_file.raw_bytes("q").unwrap(),
+            values
+                .iter()
+                .flat_map(|x| x.to_le_bytes())
+                .collect::<Vec<u8>>()
+        );
+        assert!(matches!(mat_file.raw_bytes("none"), Err(Error::Dataset(_))));
+    }
+    #[test]
+    fn packed_logical() {
+        let (path, h5) = fixture("packed_logical");
+        // 2x5 logical array packed into 2 bytes
+        let bits = [
+            true, false, true, true, false, false, false, true, true, true,
+        ];
+        let dataset = write_array::<u8>(&h5, "b", "logical", &[1, 2], &[0b1011_0001, 0b1100_0000]);
+        dataset
+            .new_attr::<usize>()
+            .shape(2)
+            .create("MATLAB_packed")
+            .unwrap()
+            .write_raw(&[5usize, 2])
+            .unwrap();
+        let dataset = write_array::<u8>(&h5, "short", "logical", &[1, 1], &[0xff]);
+        dataset
+            .new_attr::<usize>()
+            .shape(2)
+            .create("MATLAB_packed")
+            .unwrap()
+            .write_raw(&[5usize, 2])
+            .unwrap();
+        let mat_file = File::new(&path).unwrap();
+        let b = mat_file.logical_array("b").unwrap();
+        assert_eq!(b.matlab_shape(), vec![2, 5]);
+        assert_eq!(b.raw(), bits.to_vec());
+        assert!(matches!(
+            mat_file.logical_array("short"),
+            Err(Error::Shape { .. })
+        ));
+    }
+    #[test]
+    fn read_char_matrix() {
+        let (path, h5) = fixture("read_char_matrix");
+        // ['abc';'def'] in column-major order
+        let text: Vec<u16> = "adbecf".encode_utf16().collect();
+        write_array(&h5, "m", "char", &[2, 3], &text);
+        let text: Vec<u16> = "abc ".encode_utf16().collect();
+        write_array(&h5, "r", "char", &[1, 4], &text);
+        write_array(&h5, "p", "char", &[1, 2, 2], &text);
+        let mat_file = File::new(&path).unwrap();
+        assert!(matches!(mat_file.read("m"), Ok(MatValue::Char(m)) if m == "abc\ndef"));
+        assert!(matches!(mat_file.read("r"), Ok(MatValue::Char(r)) if r == "abc "));
+        assert!(matches!(mat_file.read("p"), Err(Error::Shape { .. })));
+    }
+    #[test]
+    fn char_matrix_field() {
+        let (path, h5) = fixture("char_matrix_field");
+        let group = write_struct(&h5, "s", &["names"]);
+        // ['ab ';'cde'] in column-major order
+        let text: Vec<u16> = "acbd e".encode_utf16().collect();
+        write_array(&group, "names", "char", &[2, 3], &text);
+        let mat_file = File::new(&path).unwrap();
+        let s = mat_file.structure("s").unwrap();
+        assert_eq!(s.string_field("names").unwrap(), "ab\ncde");
+        assert!(matches!(
+            &s.flatten().unwrap()["names"],
+            MatValue::Char(names) if names == "ab\ncde"
+        ));
+    }
+    #[test]
+    fn shape_errors_in_matlab_order() {
+        let file = "examples/arrays.mat";
+        let mat_file = File::new(file).unwrap();
+        let q = mat_file.array::<f64>("q").unwrap();
+        assert!(matches!(
+            q.diagonal(),
+            Err(Error::Shape { found, .. }) if found == vec![2, 3]
+        ));
+        assert!(matches!(
+            mat_file.array_shaped::<f64>("q", &[3, 2]),
+            Err(Error::Shape { found, .. }) if found == vec![2, 3]
+        ));
+    }
+    #[test]
+    fn external_link_dataset() {
+        let (target, h5) = fixture("external_target");
+        // a link value longer than any fixed buffer
+        let object = "x".repeat(5000);
+        write_array::<f64>(&h5, &object, "double", &[1, 2], &[1., 2.]);
+        h5.close().unwrap();
+        let (path, h5) = fixture("external_link");
+        let target_name = target.file_name().unwrap().to_str().unwrap();
+        write_external_link(&h5, "e", target_name, &format!("/{}", object));
+        write_external_link(&h5, "gone", "mat73_missing.mat", "/x");
+        let mat_file = File::new(&path).unwrap();
+        assert_eq!(
+            external_link(&mat_file.h5, "e"),
+            Some((target_name.to_string(), format!("/{}", object)))
+        );
+        assert_eq!(mat_file.array::<f64>("e").unwrap().data, vec![1., 2.]);
+        assert!(matches!(
+            mat_file.array::<f64>("gone"),
+            Err(Error::ExternalLink { .. })
+        ));
     }
 }